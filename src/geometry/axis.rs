@@ -1,4 +1,7 @@
-use crate::vectors::vector3::Vector3;
+use crate::vectors::{
+    ops::{MatrixCross, MatrixDot},
+    vector3::Vector3,
+};
 
 pub struct Axes3D {
     pub u: Vector3,
@@ -12,6 +15,28 @@ impl Axes3D {
         v: Vector3::<f32>::UNIT_Y,
         w: Vector3::<f32>::UNIT_Z,
     };
+
+    /// Build an orthonormal basis whose `w` is the given unit vector.
+    pub fn around(w: Vector3) -> Axes3D {
+        let a = if w.x.abs() > 0.9 {
+            Vector3::<f32>::UNIT_Y
+        } else {
+            Vector3::<f32>::UNIT_X
+        };
+        let v = w.cross(&a).to_unit();
+        let u = w.cross(&v);
+        Axes3D { u, v, w }
+    }
+
+    /// Express the vector in this (orthonormal) basis.
+    pub fn world_to_local(&self, vec: &Vector3) -> Vector3 {
+        Vector3::from((vec.dot(&self.u), vec.dot(&self.v), vec.dot(&self.w)))
+    }
+
+    /// Inverse of `world_to_local`.
+    pub fn local_to_world(&self, vec: &Vector3) -> Vector3 {
+        self.u * vec.x + self.v * vec.y + self.w * vec.z
+    }
 }
 
 impl Copy for Axes3D {}
@@ -0,0 +1,14 @@
+pub mod color;
+pub mod geometry;
+pub mod interval;
+pub mod materials;
+pub mod objects;
+pub mod optical;
+pub mod renderers;
+pub mod samplers;
+pub mod sdf;
+pub mod spectrum;
+pub mod textures;
+pub mod vectors;
+pub mod volumes;
+pub mod world;
@@ -1,32 +1,39 @@
-mod color;
-mod geometry;
-mod interval;
-mod materials;
-mod objects;
-mod optical;
-mod renderers;
-mod vectors;
-mod world;
-
-use color::Color;
 use env_logger;
-use geometry::axis::Axes3D;
-use geometry::coordinate::CoordinateSystem;
-use materials::dielectric::DiElectric;
-use materials::lambertian::Lambertian;
-use materials::material::{MaterialContainer, Materials};
-use materials::metal::Metal;
-use objects::hittables::Hittables;
-use objects::object::Object;
-use objects::plane::Plane;
-use objects::sphere::Sphere;
 use rand::random;
-use renderers::camera::{Camera, CameraGeometryParam, CameraOpticalParam, ImageSize};
-use renderers::renderer::Renderer;
+use rust_tutorial::color::Color;
+use rust_tutorial::geometry::axis::Axes3D;
+use rust_tutorial::geometry::coordinate::CoordinateSystem;
+use rust_tutorial::materials::dielectric::DiElectric;
+use rust_tutorial::materials::lambertian::Lambertian;
+use rust_tutorial::materials::material::{MaterialContainer, Materials};
+use rust_tutorial::materials::metal::Metal;
+use rust_tutorial::objects::hittables::Hittables;
+use rust_tutorial::objects::object::Object;
+use rust_tutorial::objects::plane::Plane;
+use rust_tutorial::objects::sphere::Sphere;
+use rust_tutorial::optical::aperture::{Aperture, ApertureImage};
+use rust_tutorial::renderers::camera::{
+    Camera, CameraGeometryParam, CameraOpticalParam, ImageSize, Projection,
+};
+use rust_tutorial::renderers::camera_model::{CameraModel, CameraView};
+use rust_tutorial::renderers::filter::Filter;
+use rust_tutorial::renderers::panoramic::{
+    CubemapCamera, EquirectangularCamera, FisheyeCamera, FisheyeMapping,
+};
+use rust_tutorial::renderers::renderer::Renderer;
+use rust_tutorial::renderers::statistics::StatisticsReport;
+use rust_tutorial::renderers::stereo::{StereoCamera, StereoLayout, StereoProjection};
+use rust_tutorial::samplers::blue_noise::BlueNoiseSampler;
+use rust_tutorial::samplers::halton::HaltonSampler;
+use rust_tutorial::samplers::independent::IndependentSampler;
+use rust_tutorial::samplers::sampler::Sampler;
+use rust_tutorial::samplers::sobol::SobolSampler;
+use rust_tutorial::samplers::stratified::StratifiedSampler;
+use rust_tutorial::vectors::vector3::{Point3, Vector3};
+use rust_tutorial::world::World;
 use std::io::Write;
+use std::path::Path;
 use uuid::Uuid;
-use vectors::vector3::{Point3, Vector3};
-use world::World;
 
 use once_cell::sync::Lazy;
 
@@ -36,6 +43,12 @@ const SAMPLES_PER_PIXEL: u32 = 512;
 const MAX_DEPTH: i32 = 100;
 const MAX_WORKERS: usize = 16;
 
+const USAGE: &str = "usage: rust-tutorial [--spectral] [--stats | --stats-json] \
+    [--sampler independent|stratified|halton|sobol|blue-noise] \
+    [--filter box|tent|gaussian|mitchell|lanczos] \
+    [--camera perspective|orthographic|equirectangular|fisheye|cubemap|stereo] \
+    [--aperture-blades <count> | --aperture-image <path>] [--cat-eye <shift>]";
+
 static WORLD: Lazy<World> = Lazy::new(|| initialize_world());
 
 fn initialize_world() -> World {
@@ -47,6 +60,8 @@ fn initialize_world() -> World {
     });
     let material_center = MaterialContainer::from(DiElectric {
         index_of_refraction: 1.5, // vs air: glass = 1.3-1.7, diamond = 2.4
+        absorption: Color::zero(),
+        thin_film: None,
     });
     let material_left = MaterialContainer::from(Lambertian {
         albedo: Color::from((0.4, 0.2, 0.1)),
@@ -140,6 +155,8 @@ fn initialize_world() -> World {
                 } else {
                     MaterialContainer::from(DiElectric {
                         index_of_refraction: 1.5,
+                        absorption: Color::zero(),
+                        thin_film: None,
                     })
                 };
                 objects.insert(Object::from(Sphere {
@@ -155,6 +172,143 @@ fn initialize_world() -> World {
     World { objects, materials }
 }
 
+/// Whether the flag was given on the command line.
+fn has_flag(name: &str) -> bool {
+    std::env::args().skip(1).any(|arg| arg == name)
+}
+
+/// Print the error and the usage, then exit.
+fn usage_error(message: &str) -> ! {
+    eprintln!("{}", message);
+    eprintln!("{}", USAGE);
+    std::process::exit(2);
+}
+
+/// Value following the option on the command line, if the option is given.
+fn option_value(name: &str) -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    args.find(|arg| arg == name)?;
+    match args.next() {
+        Some(value) => Some(value),
+        None => usage_error(&format!("missing value of {}", name)),
+    }
+}
+
+/// `--sampler independent|stratified|halton|sobol|blue-noise`
+fn sampler_option() -> Box<dyn Sampler> {
+    match option_value("--sampler").as_deref() {
+        None | Some("independent") => Box::new(IndependentSampler),
+        Some("stratified") => Box::new(StratifiedSampler::new(SAMPLES_PER_PIXEL, 0)),
+        Some("halton") => Box::new(HaltonSampler::new(0)),
+        Some("sobol") => Box::new(SobolSampler::new(0)),
+        Some("blue-noise") => Box::new(BlueNoiseSampler::new(0)),
+        Some(other) => usage_error(&format!("unknown sampler {}", other)),
+    }
+}
+
+/// `--filter box|tent|gaussian|mitchell|lanczos`
+fn filter_option() -> Filter {
+    match option_value("--filter").as_deref() {
+        None | Some("box") => Filter::default(),
+        Some("tent") => Filter::Tent { radius: 1.0 },
+        Some("gaussian") => Filter::Gaussian {
+            radius: 1.5,
+            sigma: 0.5,
+        },
+        Some("mitchell") => Filter::MitchellNetravali {
+            radius: 2.0,
+            b: 1.0 / 3.0,
+            c: 1.0 / 3.0,
+        },
+        Some("lanczos") => Filter::Lanczos { radius: 3.0 },
+        Some(other) => usage_error(&format!("unknown filter {}", other)),
+    }
+}
+
+/// `--aperture-blades <count>` or `--aperture-image <path>`, a disk
+/// otherwise.
+fn aperture_option() -> Aperture {
+    if let Some(count) = option_value("--aperture-blades") {
+        match count.parse() {
+            Ok(count) if count >= 3 => Aperture::Blades {
+                count,
+                rotation_deg: 0.0,
+            },
+            _ => usage_error(&format!("invalid blade count {}, at least 3", count)),
+        }
+    } else if let Some(path) = option_value("--aperture-image") {
+        match ApertureImage::load(Path::new(&path)) {
+            Ok(image) => Aperture::Image(image),
+            Err(error) => usage_error(&format!("failed to load {}: {}", path, error)),
+        }
+    } else {
+        Aperture::Disk
+    }
+}
+
+/// `--cat-eye <shift>` of the lens barrel per half image height, in
+/// aperture radii. 0 (no vignetting) otherwise.
+fn cat_eye_option() -> f32 {
+    let Some(value) = option_value("--cat-eye") else {
+        return 0.0;
+    };
+    match value.parse::<f32>() {
+        Ok(shift) if shift >= 0.0 && shift.is_finite() => shift,
+        _ => usage_error(&format!("invalid cat's-eye shift {}", value)),
+    }
+}
+
+fn camera_geometry() -> CameraGeometryParam {
+    CameraGeometryParam {
+        center: Point3::from((4.0, 4.0, 10.0)),
+        lookat: Point3::from((0.0, 0.0, 0.0)),
+        up: Vector3::from((0.0, 1.0, 0.0)),
+    }
+}
+
+/// `--camera perspective|orthographic|equirectangular|fisheye|cubemap|stereo`.
+/// Returns the projection of the thin lens camera and the camera model
+/// replacing it, if any.
+fn camera_option() -> (Projection, Option<Box<dyn CameraModel>>) {
+    let model: Box<dyn CameraModel> = match option_value("--camera").as_deref() {
+        None | Some("perspective") => return (Projection::Perspective, None),
+        Some("orthographic") => return (Projection::Orthographic { view_height: 8.0 }, None),
+        Some("equirectangular") => Box::new(EquirectangularCamera {
+            view: CameraView::from(camera_geometry()),
+            image_size: ImageSize {
+                aspect_ratio: 2.0,
+                width: IMAGE_WIDTH,
+            },
+        }),
+        Some("fisheye") => Box::new(FisheyeCamera {
+            view: CameraView::from(camera_geometry()),
+            image_size: ImageSize {
+                aspect_ratio: 1.0,
+                width: IMAGE_WIDTH,
+            },
+            mapping: FisheyeMapping::Equisolid,
+            fov_deg: 180.0,
+        }),
+        Some("cubemap") => Box::new(CubemapCamera {
+            view: CameraView::from(camera_geometry()),
+            face_size: IMAGE_WIDTH / 6,
+        }),
+        Some("stereo") => Box::new(StereoCamera {
+            view: CameraView::from(camera_geometry()),
+            eye_size: ImageSize {
+                aspect_ratio: ASPECT_RATIO,
+                width: IMAGE_WIDTH / 2,
+            },
+            projection: StereoProjection::Perspective { vfov_deg: 45.0 },
+            layout: StereoLayout::SideBySide,
+            interocular_distance: 0.064,
+            convergence_distance: 12.0,
+        }),
+        Some(other) => usage_error(&format!("unknown camera {}", other)),
+    };
+    (Projection::Perspective, Some(model))
+}
+
 fn main() {
     // render
     env_logger::builder()
@@ -162,28 +316,41 @@ fn main() {
         .format(|buf, record| write!(buf, "{}", record.args()))
         .init();
 
-    let camera_center = Point3::from((4.0, 4.0, 10.0));
-    let camera_lookat = Point3::from((0.0, 0.0, 0.0));
-    let camera_up = Vector3::from((0.0, 1.0, 0.0));
     let camera_fov = 45.0;
+    let (projection, model) = camera_option();
     let camera = Camera {
         image_size: ImageSize {
             aspect_ratio: ASPECT_RATIO,
             width: IMAGE_WIDTH,
         },
-        geometry: CameraGeometryParam {
-            center: camera_center,
-            lookat: camera_lookat,
-            up: camera_up,
-        },
+        geometry: camera_geometry(),
         optical_params: CameraOpticalParam {
+            projection,
             vfov_deg: camera_fov,
             focus_dist: 12.0,
+            aperture: aperture_option(),
+            cat_eye: cat_eye_option(),
             defocus_angle: 0.6,
         },
         samples_per_pixel: SAMPLES_PER_PIXEL,
         max_depth: MAX_DEPTH,
         max_workers: MAX_WORKERS,
+        spectral: has_flag("--spectral"),
+        aovs: Vec::new(),
+        adaptive: None,
+        progressive: None,
+        checkpoint: None,
+        statistics: if has_flag("--stats-json") {
+            Some(StatisticsReport::Json)
+        } else if has_flag("--stats") {
+            Some(StatisticsReport::Text)
+        } else {
+            None
+        },
+        filter: filter_option(),
+        sampler: sampler_option(),
+        denoiser: None,
+        model,
     };
 
     let img = camera.render(&WORLD);
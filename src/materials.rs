@@ -1,5 +1,16 @@
+pub mod bump_mapped;
+pub mod conductor;
 pub mod dielectric;
+pub mod dispersive_dielectric;
 pub mod empty;
+pub mod henyey_greenstein;
+pub mod isotropic;
 pub mod lambertian;
+pub mod layered;
 pub mod material;
 pub mod metal;
+pub mod normal_mapped;
+pub mod principled;
+pub mod rough_dielectric;
+pub mod subsurface;
+pub mod thin_dielectric;
@@ -0,0 +1,120 @@
+use crate::{
+    color::Color, objects::hittable::HitRecord, optical::ray::Ray,
+    spectrum::sampled::SampledWavelengths, textures::image_texture::ImageTexture,
+    vectors::vector3::Vector3,
+};
+
+use super::{
+    material::{Material, Scatter, SpectralScatter},
+    normal_mapped::{facing, outward_normal, with_shading_normal},
+};
+
+/// Perturb the shading normal of the base material with the gradient of a
+/// grayscale height map.
+pub struct BumpMapped {
+    pub base: Box<dyn Material + Send>,
+    pub height_map: ImageTexture,
+    /// Height of white relative to black, in world units.
+    pub scale: f32,
+}
+
+impl BumpMapped {
+    fn shade(&self, ray: &Ray, hit_record: &HitRecord) -> HitRecord {
+        let (u, v) = hit_record.uv;
+        let (du, dv) = self.height_map.texel_size();
+        let dh_du = (self.height_map.sample_gray(u + du, v)
+            - self.height_map.sample_gray(u - du, v))
+            / (2.0 * du);
+        let dh_dv = (self.height_map.sample_gray(u, v + dv)
+            - self.height_map.sample_gray(u, v - dv))
+            / (2.0 * dv);
+
+        // Height gradient over the surface rather than over uv, so the
+        // bumps keep their slope on objects of any size.
+        let (length_u, length_v) = hit_record.uv_lengths;
+        let gradient = hit_record.tangent * (dh_du / length_u.max(f32::EPSILON))
+            + hit_record.bitangent * (dh_dv / length_v.max(f32::EPSILON));
+        let norm = (outward_normal(hit_record) - gradient * self.scale).to_unit();
+        with_shading_normal(ray, hit_record, facing(hit_record, norm))
+    }
+}
+
+impl Material for BumpMapped {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<Scatter> {
+        self.base.scatter(ray, &self.shade(ray, hit_record))
+    }
+
+    fn scatter_spectral(
+        &self,
+        ray: &Ray,
+        hit_record: &HitRecord,
+        wavelengths: &mut SampledWavelengths,
+    ) -> Option<SpectralScatter> {
+        self.base
+            .scatter_spectral(ray, &self.shade(ray, hit_record), wavelengths)
+    }
+
+    fn emitted(&self, ray: &Ray, hit_record: &HitRecord) -> Color {
+        self.base.emitted(ray, hit_record)
+    }
+
+    fn eval(&self, ray: &Ray, hit_record: &HitRecord, direction: &Vector3) -> Color {
+        self.base.eval(ray, &self.shade(ray, hit_record), direction)
+    }
+
+    fn pdf(&self, ray: &Ray, hit_record: &HitRecord, direction: &Vector3) -> f32 {
+        self.base.pdf(ray, &self.shade(ray, hit_record), direction)
+    }
+
+    fn is_specular(&self) -> bool {
+        self.base.is_specular()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgba, Rgba32FImage};
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{materials::lambertian::Lambertian, vectors::vector3::Point3};
+
+    #[test]
+    fn should_mirror_the_bump_on_back_faces() {
+        // height rising by 1 per unit of u
+        let image = Rgba32FImage::from_fn(16, 1, |x, _| {
+            let height = x as f32 / 16.0;
+            Rgba([height, height, height, 1.0])
+        });
+        let material = BumpMapped {
+            base: Box::new(Lambertian::from(Color::from((0.5, 0.5, 0.5)))),
+            height_map: ImageTexture { image },
+            scale: 1.0,
+        };
+        let shade = |front_face: bool| {
+            let side = if front_face { 1.0 } else { -1.0 };
+            let hit_record = HitRecord {
+                point: Point3::zero(),
+                norm: Vector3::from((0.0, 0.0, side)),
+                front_face,
+                t: 1.0,
+                material_id: Uuid::nil(),
+                uv: (0.5, 0.5),
+                tangent: Vector3::from((1.0, 0.0, 0.0)),
+                bitangent: Vector3::from((0.0, 1.0, 0.0)),
+                // u spans 2 units of length, halving the slope
+                uv_lengths: (2.0, 1.0),
+            };
+            let ray = Ray::from((
+                Point3::from((0.0, 0.0, side)),
+                Vector3::from((0.0, 0.0, -side)),
+            ));
+            material.shade(&ray, &hit_record).norm
+        };
+
+        let (front, back) = (shade(true), shade(false));
+        let expected = Vector3::from((-0.5, 0.0, 1.0)).to_unit();
+        assert!((front - expected).norm() < 1e-3, "{} {}", front.x, front.z);
+        assert!((back + expected).norm() < 1e-3, "{} {}", back.x, back.z);
+    }
+}
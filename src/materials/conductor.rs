@@ -0,0 +1,199 @@
+use crate::{
+    color::Color,
+    geometry::axis::Axes3D,
+    objects::hittable::HitRecord,
+    optical::{
+        microfacet::Ggx,
+        ray::Ray,
+        scatter::{fresnel_conductor, reflect},
+        thin_film::{rgb_at_wavelength, ThinFilm},
+    },
+    samplers::sampler::sample_2d,
+    spectrum::sampled::{SampledSpectrum, SampledWavelengths},
+    vectors::ops::MatrixDot,
+};
+
+use super::material::{Material, Scatter, SpectralScatter};
+
+/// Physically based rough metal: GGX microfacet reflection with the exact
+/// Fresnel term of a conductor with complex index of refraction `eta + i k`
+/// (per color channel).
+pub struct Conductor {
+    pub eta: Color,
+    pub k: Color,
+    /// Perceptual roughness in [0, 1]. 0 is a perfect mirror.
+    pub roughness: f32,
+    /// Optional thin film (e.g. oxide layer, anodizing) on the metal.
+    pub thin_film: Option<ThinFilm>,
+}
+
+impl Conductor {
+    pub fn gold(roughness: f32) -> Self {
+        Conductor {
+            eta: Color::from((0.143, 0.374, 1.442)),
+            k: Color::from((3.983, 2.385, 1.603)),
+            roughness,
+            thin_film: None,
+        }
+    }
+    pub fn copper(roughness: f32) -> Self {
+        Conductor {
+            eta: Color::from((0.200, 0.924, 1.102)),
+            k: Color::from((3.912, 2.452, 2.142)),
+            roughness,
+            thin_film: None,
+        }
+    }
+    pub fn aluminium(roughness: f32) -> Self {
+        Conductor {
+            eta: Color::from((1.657, 0.880, 0.521)),
+            k: Color::from((9.224, 6.270, 4.837)),
+            roughness,
+            thin_film: None,
+        }
+    }
+    pub fn silver(roughness: f32) -> Self {
+        Conductor {
+            eta: Color::from((0.155, 0.117, 0.138)),
+            k: Color::from((4.828, 3.122, 2.147)),
+            roughness,
+            thin_film: None,
+        }
+    }
+}
+
+impl Conductor {
+    /// Sample a microfacet reflection. Returns the cosine between the
+    /// outgoing direction and the microfacet normal, the weight without the
+    /// Fresnel term, and the reflected ray.
+    fn sample_reflection(&self, ray: &Ray, hit_record: &HitRecord) -> Option<(f32, f32, Ray)> {
+        let unit_direction = ray.direction.to_unit();
+        let frame = Axes3D::around(hit_record.norm);
+        let wo = frame.world_to_local(&-unit_direction);
+        if wo.z <= 0.0 {
+            return None;
+        }
+
+        let ggx = Ggx::from_roughness(self.roughness);
+        let (u1, u2) = sample_2d();
+        let m = ggx.sample_visible_normal(&wo, u1, u2);
+        let wi = reflect(&-wo, &m);
+        if wi.z <= 0.0 {
+            // reflected below the surface
+            return None;
+        }
+
+        // With visible normal sampling the weight f * cos / pdf reduces to
+        // F * G2 / G1.
+        let ray = Ray {
+            origin: hit_record.point,
+            direction: frame.local_to_world(&wi),
+        };
+        Some((wo.dot(&m), ggx.g2(&wo, &wi) / ggx.g1(&wo), ray))
+    }
+
+    /// Complex index of refraction at the wavelength.
+    fn index_at(&self, lambda: f32) -> (f32, f32) {
+        (
+            rgb_at_wavelength(&self.eta, lambda),
+            rgb_at_wavelength(&self.k, lambda),
+        )
+    }
+}
+
+impl Material for Conductor {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<Scatter> {
+        let (cos_theta, weight, ray) = self.sample_reflection(ray, hit_record)?;
+        let fresnel = match &self.thin_film {
+            Some(film) => film.reflectance_rgb(
+                cos_theta,
+                film.thickness_at(hit_record.uv),
+                1.0,
+                &|lambda| self.index_at(lambda),
+            ),
+            None => fresnel_conductor(cos_theta, self.eta, self.k),
+        };
+        Some(Scatter {
+            attenuation: fresnel * weight,
+            ray,
+        })
+    }
+
+    fn scatter_spectral(
+        &self,
+        ray: &Ray,
+        hit_record: &HitRecord,
+        wavelengths: &mut SampledWavelengths,
+    ) -> Option<SpectralScatter> {
+        let Some(film) = &self.thin_film else {
+            let scattered = self.scatter(ray, hit_record)?;
+            return Some(SpectralScatter {
+                attenuation: SampledSpectrum::from_rgb(&scattered.attenuation, wavelengths),
+                ray: scattered.ray,
+            });
+        };
+
+        let (cos_theta, weight, ray) = self.sample_reflection(ray, hit_record)?;
+        let thickness = film.thickness_at(hit_record.uv);
+        let attenuation = SampledSpectrum {
+            values: wavelengths.lambda.map(|lambda| {
+                film.reflectance(cos_theta, lambda, thickness, 1.0, self.index_at(lambda)) * weight
+            }),
+        };
+        Some(SpectralScatter { attenuation, ray })
+    }
+
+    fn is_specular(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vectors::vector3::{Point3, Vector3};
+    use uuid::Uuid;
+
+    /// Reflectance at normal incidence of the complex index of refraction.
+    fn normal_reflectance(eta: f32, k: f32) -> f32 {
+        ((eta - 1.0).powi(2) + k * k) / ((eta + 1.0).powi(2) + k * k)
+    }
+
+    #[test]
+    fn should_reflect_tabulated_reflectance_at_normal_incidence() {
+        let hit_record = HitRecord {
+            point: Point3::zero(),
+            norm: Vector3::from((0.0, 0.0, 1.0)),
+            front_face: true,
+            t: 1.0,
+            material_id: Uuid::nil(),
+            uv: (0.0, 0.0),
+            tangent: Vector3::from((1.0, 0.0, 0.0)),
+            bitangent: Vector3::from((0.0, 1.0, 0.0)),
+            uv_lengths: (1.0, 1.0),
+        };
+        let ray = Ray {
+            origin: Point3::from((0.0, 0.0, 1.0)),
+            direction: Vector3::from((0.0, 0.0, -1.0)),
+        };
+        for metal in [
+            Conductor::gold(0.0),
+            Conductor::copper(0.0),
+            Conductor::aluminium(0.0),
+            Conductor::silver(0.0),
+        ] {
+            let scattered = metal.scatter(&ray, &hit_record).unwrap();
+            assert!(scattered.ray.direction.to_unit().z > 0.999);
+            for channel in 0..3 {
+                let expected = normal_reflectance(metal.eta[channel], metal.k[channel]);
+                let actual = scattered.attenuation[channel];
+                assert!(
+                    (actual - expected).abs() < 1.0e-3,
+                    "{} != {}",
+                    actual,
+                    expected
+                );
+            }
+        }
+    }
+}
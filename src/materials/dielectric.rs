@@ -1,38 +1,158 @@
 use crate::{
     color::Color,
-    optical::{ray::Ray, scatter::refract_or_reflect},
+    objects::hittable::HitRecord,
+    optical::{
+        ray::Ray,
+        scatter::{beer_lambert, reflect, refract, refract_or_reflect},
+        thin_film::ThinFilm,
+    },
+    samplers::sampler::sample_1d,
+    spectrum::sampled::{SampledSpectrum, SampledWavelengths, N_SAMPLES},
+    vectors::{ops::MatrixDot, vector3::Vector3},
 };
 
-use super::material::{Material, Scatter};
+use super::material::{Material, Scatter, SpectralScatter};
 
 pub struct DiElectric {
     pub index_of_refraction: f32,
+    /// Absorption coefficient per unit length inside of the object
+    /// (Beer-Lambert law). Zero for clear glass.
+    pub absorption: Color,
+    /// Optional thin film coating the surface. The Fresnel term is then
+    /// evaluated per wavelength, giving interference colors.
+    pub thin_film: Option<ThinFilm>,
+}
+
+impl DiElectric {
+    /// Rays hitting the back face have travelled through the object.
+    fn transmittance(&self, ray: &Ray, hit_record: &HitRecord) -> Color {
+        if hit_record.front_face {
+            Color::from((1.0, 1.0, 1.0))
+        } else {
+            beer_lambert(self.absorption, hit_record.t * ray.direction.norm())
+        }
+    }
+
+    /// Indices of refraction of the incident medium and of the substrate
+    /// under the film.
+    fn indices(&self, hit_record: &HitRecord) -> (f32, f32) {
+        if hit_record.front_face {
+            (1.0, self.index_of_refraction)
+        } else {
+            (self.index_of_refraction, 1.0)
+        }
+    }
+
+    /// Reflect with probability `reflection_probability`, otherwise
+    /// refract. Returns the direction and whether the choice was random,
+    /// i.e. not forced by total internal reflection.
+    fn choose_direction(
+        &self,
+        unit_direction: &Vector3,
+        hit_record: &HitRecord,
+        reflection_probability: f32,
+    ) -> (Vector3, Option<bool>) {
+        let (n_incident, n_substrate) = self.indices(hit_record);
+        let reflected = reflect(unit_direction, &hit_record.norm);
+        match refract(unit_direction, &hit_record.norm, n_incident / n_substrate) {
+            None => (reflected, None),
+            Some(_) if reflection_probability > sample_1d() => (reflected, Some(true)),
+            Some(refracted) => (refracted, Some(false)),
+        }
+    }
 }
 
 impl Material for DiElectric {
-    fn scatter(
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<Scatter> {
+        let unit_direction = ray.direction.to_unit();
+        let transmittance = self.transmittance(ray, hit_record);
+
+        let Some(film) = &self.thin_film else {
+            let refraction_ratio = if hit_record.front_face {
+                1.0 / self.index_of_refraction
+            } else {
+                self.index_of_refraction
+            };
+            let scattered_direction =
+                refract_or_reflect(&unit_direction, &hit_record.norm, refraction_ratio);
+            return Some(Scatter {
+                attenuation: transmittance,
+                ray: Ray {
+                    origin: hit_record.point,
+                    direction: scattered_direction,
+                },
+            });
+        };
+
+        let (n_incident, n_substrate) = self.indices(hit_record);
+        let cos_theta = -unit_direction.dot(&hit_record.norm);
+        let thickness = film.thickness_at(hit_record.uv);
+        let reflectance =
+            film.reflectance_rgb(cos_theta, thickness, n_incident, &|_| (n_substrate, 0.0));
+        let probability = (reflectance.x + reflectance.y + reflectance.z) / 3.0;
+
+        let (direction, reflected) =
+            self.choose_direction(&unit_direction, hit_record, probability);
+        let weight = match reflected {
+            Some(true) => reflectance / probability,
+            Some(false) => (Color::from((1.0, 1.0, 1.0)) - reflectance) / (1.0 - probability),
+            // total internal reflection
+            None => Color::from((1.0, 1.0, 1.0)),
+        };
+        Some(Scatter {
+            attenuation: transmittance * weight,
+            ray: Ray {
+                origin: hit_record.point,
+                direction,
+            },
+        })
+    }
+
+    fn scatter_spectral(
         &self,
         ray: &Ray,
-        hit_record: &crate::objects::hittable::HitRecord,
-    ) -> Option<super::material::Scatter> {
-        let refraction_ratio = if hit_record.front_face {
-            1.0 / self.index_of_refraction
-        } else {
-            self.index_of_refraction
+        hit_record: &HitRecord,
+        wavelengths: &mut SampledWavelengths,
+    ) -> Option<SpectralScatter> {
+        let Some(film) = &self.thin_film else {
+            let scattered = self.scatter(ray, hit_record)?;
+            return Some(SpectralScatter {
+                attenuation: SampledSpectrum::from_rgb(&scattered.attenuation, wavelengths),
+                ray: scattered.ray,
+            });
         };
 
         let unit_direction = ray.direction.to_unit();
-        let scattered_direction =
-            refract_or_reflect(&unit_direction, &hit_record.norm, refraction_ratio);
+        let (n_incident, n_substrate) = self.indices(hit_record);
+        let cos_theta = -unit_direction.dot(&hit_record.norm);
+        let thickness = film.thickness_at(hit_record.uv);
+        let mut reflectance = [0.0; N_SAMPLES];
+        for (r, lambda) in reflectance.iter_mut().zip(wavelengths.lambda) {
+            *r = film.reflectance(cos_theta, lambda, thickness, n_incident, (n_substrate, 0.0));
+        }
+        let probability = reflectance.iter().sum::<f32>() / N_SAMPLES as f32;
 
-        let scattered = Ray {
-            origin: hit_record.point,
-            direction: scattered_direction,
+        let (direction, reflected) =
+            self.choose_direction(&unit_direction, hit_record, probability);
+        let weight = SampledSpectrum {
+            values: reflectance.map(|r| match reflected {
+                Some(true) => r / probability,
+                Some(false) => (1.0 - r) / (1.0 - probability),
+                None => 1.0,
+            }),
         };
-        let attenuation = Color::from((1.0, 1.0, 1.0));
-        Some(Scatter {
-            attenuation,
-            ray: scattered,
+        let transmittance =
+            SampledSpectrum::from_rgb(&self.transmittance(ray, hit_record), wavelengths);
+        Some(SpectralScatter {
+            attenuation: transmittance * weight,
+            ray: Ray {
+                origin: hit_record.point,
+                direction,
+            },
         })
     }
+
+    fn is_specular(&self) -> bool {
+        true
+    }
 }
@@ -0,0 +1,114 @@
+use crate::{
+    color::Color,
+    objects::hittable::HitRecord,
+    optical::{
+        dispersion::{Dispersion, LAMBDA_D},
+        ray::Ray,
+    },
+    spectrum::sampled::{SampledSpectrum, SampledWavelengths},
+};
+
+use super::{
+    dielectric::DiElectric,
+    material::{Material, Scatter, SpectralScatter},
+};
+
+/// Glass whose index of refraction depends on the wavelength, splitting
+/// white light into colors (prisms, diamonds) when rendered spectrally.
+/// In RGB rendering it behaves like `DiElectric` at the d line.
+pub struct DispersiveDielectric {
+    pub dispersion: Dispersion,
+    pub absorption: Color,
+}
+
+impl DispersiveDielectric {
+    fn at_wavelength(&self, lambda: f32) -> DiElectric {
+        DiElectric {
+            index_of_refraction: self.dispersion.index_of_refraction(lambda),
+            absorption: self.absorption,
+            thin_film: None,
+        }
+    }
+}
+
+impl Material for DispersiveDielectric {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<Scatter> {
+        self.at_wavelength(LAMBDA_D).scatter(ray, hit_record)
+    }
+
+    fn scatter_spectral(
+        &self,
+        ray: &Ray,
+        hit_record: &HitRecord,
+        wavelengths: &mut SampledWavelengths,
+    ) -> Option<SpectralScatter> {
+        // The scattered direction depends on the wavelength, so only the
+        // hero wavelength can follow it.
+        if !self.dispersion.is_constant() {
+            wavelengths.terminate_secondary();
+        }
+        let scattered = self
+            .at_wavelength(wavelengths.hero())
+            .scatter(ray, hit_record)?;
+        Some(SpectralScatter {
+            attenuation: SampledSpectrum::from_rgb(&scattered.attenuation, wavelengths),
+            ray: scattered.ray,
+        })
+    }
+
+    fn is_specular(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::vectors::vector3::{Point3, Vector3};
+
+    fn hit_record() -> HitRecord {
+        HitRecord {
+            point: Point3::zero(),
+            norm: Vector3::from((0.0, 0.0, 1.0)),
+            front_face: true,
+            t: 1.0,
+            material_id: Uuid::nil(),
+            uv: (0.0, 0.0),
+            tangent: Vector3::from((1.0, 0.0, 0.0)),
+            bitangent: Vector3::from((0.0, 1.0, 0.0)),
+            uv_lengths: (1.0, 1.0),
+        }
+    }
+
+    #[test]
+    fn should_terminate_secondary_wavelengths_when_dispersive() {
+        let ray = Ray::from((
+            Point3::from((-1.0, 0.0, 1.0)),
+            Vector3::from((1.0, 0.0, -1.0)),
+        ));
+        let glass = DispersiveDielectric {
+            dispersion: Dispersion::bk7(),
+            absorption: Color::zero(),
+        };
+        let mut wavelengths = SampledWavelengths::sample_uniform(0.3);
+        glass
+            .scatter_spectral(&ray, &hit_record(), &mut wavelengths)
+            .unwrap();
+        assert!(wavelengths.secondary_terminated());
+        assert!(wavelengths.pdf[0] > 0.0);
+        assert!(wavelengths.pdf[1..].iter().all(|pdf| *pdf == 0.0));
+
+        let glass = DispersiveDielectric {
+            dispersion: Dispersion::Constant(1.5),
+            absorption: Color::zero(),
+        };
+        let mut wavelengths = SampledWavelengths::sample_uniform(0.3);
+        glass
+            .scatter_spectral(&ray, &hit_record(), &mut wavelengths)
+            .unwrap();
+        assert!(!wavelengths.secondary_terminated());
+        assert!(wavelengths.pdf.iter().all(|pdf| *pdf > 0.0));
+    }
+}
@@ -0,0 +1,77 @@
+use std::f32::consts::PI;
+
+use crate::{
+    color::Color, geometry::axis::Axes3D, objects::hittable::HitRecord, optical::ray::Ray,
+    samplers::sampler::sample_2d, volumes::density::DensityField,
+};
+
+use super::material::{Material, Scatter};
+
+/// Anisotropic phase function of Henyey and Greenstein.
+/// `g` in (-1, 1) controls back (< 0) or forward (> 0) scattering,
+/// `g = 0` is equivalent to `Isotropic`.
+///
+/// `emission` makes the medium glow (e.g. fire). When `emission_field` is
+/// set, the emitted radiance is scaled by the field at the scattering point,
+/// which allows e.g. driving it by a temperature grid.
+pub struct HenyeyGreenstein {
+    pub albedo: Color,
+    pub g: f32,
+    pub emission: Color,
+    pub emission_field: Option<Box<dyn DensityField + Send>>,
+}
+
+impl From<(Color, f32)> for HenyeyGreenstein {
+    fn from(value: (Color, f32)) -> Self {
+        HenyeyGreenstein {
+            albedo: value.0,
+            g: value.1,
+            emission: Color::zero(),
+            emission_field: None,
+        }
+    }
+}
+
+impl HenyeyGreenstein {
+    /// Sample cosine between the incident and the scattered direction.
+    fn sample_cos_theta(&self, u: f32) -> f32 {
+        let g = self.g;
+        if g.abs() < 1.0e-3 {
+            return 1.0 - 2.0 * u;
+        }
+        let s = (1.0 - g * g) / (1.0 - g + 2.0 * g * u);
+        ((1.0 + g * g - s * s) / (2.0 * g)).clamp(-1.0, 1.0)
+    }
+}
+
+impl Material for HenyeyGreenstein {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<Scatter> {
+        let (u1, u2) = sample_2d();
+        let cos_theta = self.sample_cos_theta(u1);
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = 2.0 * PI * u2;
+
+        let axes = Axes3D::around(ray.direction.to_unit());
+        let direction = axes.u * (sin_theta * phi.cos())
+            + axes.v * (sin_theta * phi.sin())
+            + axes.w * cos_theta;
+        Some(Scatter {
+            attenuation: self.albedo,
+            ray: Ray {
+                origin: hit_record.point,
+                direction,
+            },
+        })
+    }
+
+    fn emitted(&self, _: &Ray, hit_record: &HitRecord) -> Color {
+        match &self.emission_field {
+            Some(field) => self.emission * field.density(hit_record.point),
+            None => self.emission,
+        }
+    }
+
+    fn is_volumetric(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,38 @@
+use crate::{
+    color::Color,
+    objects::hittable::HitRecord,
+    optical::ray::Ray,
+    samplers::{sampler::sample_2d, warp::unit_sphere},
+};
+
+use super::material::{Material, Scatter};
+
+/// Isotropic phase function for participating media.
+/// Scatters light uniformly in all directions.
+/// https://raytracing.github.io/books/RayTracingTheNextWeek.html#volumes
+pub struct Isotropic {
+    pub albedo: Color,
+}
+
+impl From<Color> for Isotropic {
+    fn from(albedo: Color) -> Self {
+        Isotropic { albedo }
+    }
+}
+
+impl Material for Isotropic {
+    fn scatter(&self, _: &Ray, hit_record: &HitRecord) -> Option<Scatter> {
+        let scattered = Ray {
+            origin: hit_record.point,
+            direction: unit_sphere(sample_2d()),
+        };
+        Some(Scatter {
+            attenuation: self.albedo,
+            ray: scattered,
+        })
+    }
+
+    fn is_volumetric(&self) -> bool {
+        true
+    }
+}
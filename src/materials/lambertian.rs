@@ -2,7 +2,8 @@ use crate::{
     color::Color,
     objects::hittable::HitRecord,
     optical::ray::Ray,
-    vectors::{utils::near_zero, vector3::Vector3},
+    samplers::{sampler::sample_2d, warp::unit_sphere},
+    vectors::utils::near_zero,
 };
 
 use super::material::{Material, Scatter};
@@ -21,7 +22,7 @@ impl From<Color> for Lambertian {
 
 impl Material for Lambertian {
     fn scatter(&self, _: &Ray, hit_record: &HitRecord) -> Option<Scatter> {
-        let _scatter_direction = Vector3::<f32>::random_unit_vector() + hit_record.norm;
+        let _scatter_direction = unit_sphere(sample_2d()) + hit_record.norm;
         let scatter_direction = if near_zero(&_scatter_direction) {
             hit_record.norm
         } else {
@@ -0,0 +1,167 @@
+use crate::{
+    color::Color,
+    objects::hittable::HitRecord,
+    optical::{ray::Ray, scatter::reflectance},
+    samplers::sampler::sample_1d,
+    spectrum::sampled::{SampledSpectrum, SampledWavelengths},
+    vectors::{ops::MatrixDot, vector3::Vector3},
+};
+
+use super::{
+    material::{Material, Scatter, SpectralScatter},
+    metal::Metal,
+};
+
+/// Base material under a clear dielectric coat (car paint, varnished wood).
+/// Light is reflected by the coat with the Fresnel reflectance of its
+/// interface, and otherwise reaches the base through the (tinted) coat.
+pub struct Layered {
+    /// Top layer, usually a smooth or slightly fuzzy `Metal` with white albedo.
+    pub coat: Box<dyn Material + Send>,
+    /// Bottom layer, e.g. `Lambertian` or `Metal`.
+    pub base: Box<dyn Material + Send>,
+    pub coat_index_of_refraction: f32,
+    /// Color filter of the coat. Light reaching the base passes it twice.
+    pub coat_tint: Color,
+}
+
+impl Layered {
+    /// Coat the base with clear varnish.
+    pub fn clear_coat(base: Box<dyn Material + Send>, coat_index_of_refraction: f32) -> Self {
+        Layered {
+            coat: Box::new(Metal {
+                albedo: Color::from((1.0, 1.0, 1.0)),
+                fuzzy: 0.0,
+            }),
+            base,
+            coat_index_of_refraction,
+            coat_tint: Color::from((1.0, 1.0, 1.0)),
+        }
+    }
+
+    /// Probability of the light being reflected by the coat.
+    fn coat_reflectance(&self, ray: &Ray, hit_record: &HitRecord) -> f32 {
+        let cos_theta = -ray.direction.to_unit().dot(&hit_record.norm);
+        reflectance(
+            cos_theta.clamp(0.0, 1.0),
+            1.0 / self.coat_index_of_refraction,
+        )
+    }
+
+    fn base_transmittance(&self) -> Color {
+        self.coat_tint * self.coat_tint
+    }
+}
+
+impl Material for Layered {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<Scatter> {
+        if !hit_record.front_face {
+            return self.base.scatter(ray, hit_record);
+        }
+        if sample_1d() < self.coat_reflectance(ray, hit_record) {
+            self.coat.scatter(ray, hit_record)
+        } else {
+            let scattered = self.base.scatter(ray, hit_record)?;
+            Some(Scatter {
+                attenuation: scattered.attenuation * self.base_transmittance(),
+                ray: scattered.ray,
+            })
+        }
+    }
+
+    fn scatter_spectral(
+        &self,
+        ray: &Ray,
+        hit_record: &HitRecord,
+        wavelengths: &mut SampledWavelengths,
+    ) -> Option<SpectralScatter> {
+        if !hit_record.front_face {
+            return self.base.scatter_spectral(ray, hit_record, wavelengths);
+        }
+        if sample_1d() < self.coat_reflectance(ray, hit_record) {
+            self.coat.scatter_spectral(ray, hit_record, wavelengths)
+        } else {
+            let scattered = self.base.scatter_spectral(ray, hit_record, wavelengths)?;
+            Some(SpectralScatter {
+                attenuation: scattered.attenuation
+                    * SampledSpectrum::from_rgb(&self.base_transmittance(), wavelengths),
+                ray: scattered.ray,
+            })
+        }
+    }
+
+    fn emitted(&self, ray: &Ray, hit_record: &HitRecord) -> Color {
+        self.base.emitted(ray, hit_record)
+    }
+
+    fn eval(&self, ray: &Ray, hit_record: &HitRecord, direction: &Vector3) -> Color {
+        if !hit_record.front_face {
+            return self.base.eval(ray, hit_record, direction);
+        }
+        let r = self.coat_reflectance(ray, hit_record);
+        self.coat.eval(ray, hit_record, direction) * r
+            + self.base.eval(ray, hit_record, direction) * self.base_transmittance() * (1.0 - r)
+    }
+
+    fn pdf(&self, ray: &Ray, hit_record: &HitRecord, direction: &Vector3) -> f32 {
+        if !hit_record.front_face {
+            return self.base.pdf(ray, hit_record, direction);
+        }
+        let r = self.coat_reflectance(ray, hit_record);
+        self.coat.pdf(ray, hit_record, direction) * r
+            + self.base.pdf(ray, hit_record, direction) * (1.0 - r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{materials::lambertian::Lambertian, vectors::vector3::Point3};
+    use uuid::Uuid;
+
+    #[test]
+    fn should_mirror_the_fresnel_reflectance_of_the_coat() {
+        let hit_record = HitRecord {
+            point: Point3::zero(),
+            norm: Vector3::from((0.0, 0.0, 1.0)),
+            front_face: true,
+            t: 1.0,
+            material_id: Uuid::nil(),
+            uv: (0.0, 0.0),
+            tangent: Vector3::from((1.0, 0.0, 0.0)),
+            bitangent: Vector3::from((0.0, 1.0, 0.0)),
+            uv_lengths: (1.0, 1.0),
+        };
+        let ray = Ray {
+            origin: Point3::from((0.0, 0.0, 1.0)),
+            direction: Vector3::from((0.0, 0.0, -1.0)),
+        };
+        let material = Layered::clear_coat(
+            Box::new(Lambertian::from(Color::from((0.5, 0.5, 0.5)))),
+            1.5,
+        );
+
+        let samples = 20000;
+        let mut energy = 0.0;
+        let mut mirrored = 0;
+        for _ in 0..samples {
+            let scattered = material.scatter(&ray, &hit_record).unwrap();
+            energy += scattered.attenuation.x;
+            if scattered.ray.direction.to_unit().z > 0.9999 {
+                mirrored += 1;
+            }
+        }
+        // ((1 - 1.5) / (1 + 1.5))^2 at normal incidence
+        let r0 = 0.04;
+        let mirrored = mirrored as f32 / samples as f32;
+        assert!((mirrored - r0).abs() < 0.01, "{} != {}", mirrored, r0);
+        let expected = r0 + (1.0 - r0) * 0.5;
+        let energy = energy / samples as f32;
+        assert!(
+            (energy - expected).abs() < 0.02,
+            "{} != {}",
+            energy,
+            expected
+        );
+    }
+}
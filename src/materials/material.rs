@@ -1,6 +1,12 @@
 use std::collections::HashMap;
 
-use crate::{color::Color, objects::hittable::HitRecord, optical::ray::Ray};
+use crate::{
+    color::Color,
+    objects::hittable::HitRecord,
+    optical::ray::Ray,
+    spectrum::sampled::{SampledSpectrum, SampledWavelengths},
+    vectors::vector3::Vector3,
+};
 
 use uuid::Uuid;
 
@@ -9,8 +15,59 @@ pub struct Scatter {
     pub ray: Ray,
 }
 
+/// `Scatter` in spectral rendering, with attenuation per sampled wavelength.
+pub struct SpectralScatter {
+    pub attenuation: SampledSpectrum,
+    pub ray: Ray,
+}
+
 pub trait Material {
     fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<Scatter>;
+
+    /// Scatter a path carrying the sampled wavelengths. Wavelength dependent
+    /// materials override this, others are upsampled from `scatter`.
+    fn scatter_spectral(
+        &self,
+        ray: &Ray,
+        hit_record: &HitRecord,
+        wavelengths: &mut SampledWavelengths,
+    ) -> Option<SpectralScatter> {
+        let scattered = self.scatter(ray, hit_record)?;
+        Some(SpectralScatter {
+            attenuation: SampledSpectrum::from_rgb(&scattered.attenuation, wavelengths),
+            ray: scattered.ray,
+        })
+    }
+
+    /// Radiance emitted from the hit point towards the ray origin.
+    fn emitted(&self, _ray: &Ray, _hit_record: &HitRecord) -> Color {
+        Color::zero()
+    }
+
+    /// Value of the BSDF times the cosine term for light scattered from
+    /// `direction` towards the ray origin. Black unless the material
+    /// supports evaluation (e.g. for light sampling).
+    fn eval(&self, _ray: &Ray, _hit_record: &HitRecord, _direction: &Vector3) -> Color {
+        Color::zero()
+    }
+
+    /// Probability density (per solid angle) of `scatter` choosing
+    /// `direction`. Zero unless the material supports evaluation.
+    fn pdf(&self, _ray: &Ray, _hit_record: &HitRecord, _direction: &Vector3) -> f32 {
+        0.0
+    }
+
+    /// Whether the material scatters (near) specularly, like mirrors and
+    /// glass, rather than diffusely. Used to split the render passes.
+    fn is_specular(&self) -> bool {
+        false
+    }
+
+    /// Whether this material is a phase function scattering inside of a
+    /// participating medium rather than at a surface.
+    fn is_volumetric(&self) -> bool {
+        false
+    }
 }
 
 pub struct MaterialContainer {
@@ -32,6 +89,12 @@ pub struct Materials {
     pub materials: HashMap<Uuid, MaterialContainer>,
 }
 
+impl Default for Materials {
+    fn default() -> Self {
+        Materials::new()
+    }
+}
+
 impl Materials {
     pub fn new() -> Self {
         Materials {
@@ -1,7 +1,7 @@
 use crate::{
     color::Color,
     optical::{ray::Ray, scatter::reflect},
-    vectors::vector3::Vector3,
+    samplers::{sampler::sample_2d, warp::unit_sphere},
 };
 
 use super::material::{Material, Scatter};
@@ -27,7 +27,7 @@ impl Material for Metal {
         hit_record: &crate::objects::hittable::HitRecord,
     ) -> Option<Scatter> {
         let reflected = reflect(&ray.direction.to_unit(), &hit_record.norm)
-            + Vector3::<f32>::random_unit_vector() * self.fuzzy;
+            + unit_sphere(sample_2d()) * self.fuzzy;
         let attenuation = self.albedo;
         let scattered = Ray {
             origin: hit_record.point,
@@ -38,4 +38,8 @@ impl Material for Metal {
             ray: scattered,
         })
     }
+
+    fn is_specular(&self) -> bool {
+        true
+    }
 }
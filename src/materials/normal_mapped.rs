@@ -0,0 +1,158 @@
+use crate::{
+    color::Color,
+    objects::hittable::HitRecord,
+    optical::ray::Ray,
+    spectrum::sampled::SampledWavelengths,
+    textures::image_texture::ImageTexture,
+    vectors::{ops::MatrixDot, vector3::Vector3},
+};
+
+use super::material::{Material, Scatter, SpectralScatter};
+
+/// Replace the shading normal of the hit record, unless it would face away
+/// from the incoming ray (which would make the base material scatter into
+/// the surface).
+pub fn with_shading_normal(ray: &Ray, hit_record: &HitRecord, norm: Vector3) -> HitRecord {
+    let norm = if norm.dot(&ray.direction) < 0.0 {
+        norm
+    } else {
+        hit_record.norm
+    };
+    HitRecord {
+        norm,
+        ..*hit_record
+    }
+}
+
+/// Geometric normal pointing out of the surface, whichever side was hit.
+pub fn outward_normal(hit_record: &HitRecord) -> Vector3 {
+    if hit_record.front_face {
+        hit_record.norm
+    } else {
+        -hit_record.norm
+    }
+}
+
+/// Flip a normal perturbed around `outward_normal` back to the side of the
+/// hit record.
+pub fn facing(hit_record: &HitRecord, outward: Vector3) -> Vector3 {
+    if hit_record.front_face {
+        outward
+    } else {
+        -outward
+    }
+}
+
+/// Perturb the shading normal of the base material with a tangent-space
+/// normal map (OpenGL convention: red = +u, green = +v, blue = normal).
+pub struct NormalMapped {
+    pub base: Box<dyn Material + Send>,
+    pub map: ImageTexture,
+    /// Blend between the geometric normal (0) and the mapped one (1).
+    pub strength: f32,
+}
+
+impl NormalMapped {
+    fn shade(&self, ray: &Ray, hit_record: &HitRecord) -> HitRecord {
+        let (u, v) = hit_record.uv;
+        let texel = self.map.sample(u, v) * 2.0 - 1.0;
+        let local = Vector3::from((texel.x * self.strength, texel.y * self.strength, texel.z));
+        // The tangent frame is built around the outward normal, while the
+        // normal of the hit record faces the ray.
+        let outward = outward_normal(hit_record);
+        let norm =
+            (hit_record.tangent * local.x + hit_record.bitangent * local.y + outward * local.z)
+                .to_unit();
+        with_shading_normal(ray, hit_record, facing(hit_record, norm))
+    }
+}
+
+impl Material for NormalMapped {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<Scatter> {
+        self.base.scatter(ray, &self.shade(ray, hit_record))
+    }
+
+    fn scatter_spectral(
+        &self,
+        ray: &Ray,
+        hit_record: &HitRecord,
+        wavelengths: &mut SampledWavelengths,
+    ) -> Option<SpectralScatter> {
+        self.base
+            .scatter_spectral(ray, &self.shade(ray, hit_record), wavelengths)
+    }
+
+    fn emitted(&self, ray: &Ray, hit_record: &HitRecord) -> Color {
+        self.base.emitted(ray, hit_record)
+    }
+
+    fn eval(&self, ray: &Ray, hit_record: &HitRecord, direction: &Vector3) -> Color {
+        self.base.eval(ray, &self.shade(ray, hit_record), direction)
+    }
+
+    fn pdf(&self, ray: &Ray, hit_record: &HitRecord, direction: &Vector3) -> f32 {
+        self.base.pdf(ray, &self.shade(ray, hit_record), direction)
+    }
+
+    fn is_specular(&self) -> bool {
+        self.base.is_specular()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgba, Rgba32FImage};
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{materials::lambertian::Lambertian, vectors::vector3::Point3};
+
+    fn normal_mapped(texel: [f32; 3]) -> NormalMapped {
+        NormalMapped {
+            base: Box::new(Lambertian::from(Color::from((0.5, 0.5, 0.5)))),
+            map: ImageTexture {
+                image: Rgba32FImage::from_pixel(1, 1, Rgba([texel[0], texel[1], texel[2], 1.0])),
+            },
+            strength: 1.0,
+        }
+    }
+
+    fn hit_record() -> HitRecord {
+        HitRecord {
+            point: Point3::zero(),
+            norm: Vector3::from((0.0, 0.0, 1.0)),
+            front_face: true,
+            t: 1.0,
+            material_id: Uuid::nil(),
+            uv: (0.5, 0.5),
+            tangent: Vector3::from((0.0, 1.0, 0.0)),
+            bitangent: Vector3::from((-1.0, 0.0, 0.0)),
+            uv_lengths: (1.0, 1.0),
+        }
+    }
+
+    #[test]
+    fn should_keep_the_normal_of_a_flat_map() {
+        let material = normal_mapped([0.5, 0.5, 1.0]);
+        let ray = Ray::from((
+            Point3::from((0.0, 0.0, 1.0)),
+            Vector3::from((0.0, 0.0, -1.0)),
+        ));
+        let shaded = material.shade(&ray, &hit_record());
+        assert!((shaded.norm - hit_record().norm).norm() < 1e-6);
+    }
+
+    #[test]
+    fn should_tilt_the_normal_in_the_tangent_frame() {
+        let half = std::f32::consts::FRAC_1_SQRT_2;
+        // 45 degrees towards +u
+        let material = normal_mapped([0.5 + 0.5 * half, 0.5, 0.5 + 0.5 * half]);
+        let ray = Ray::from((
+            Point3::from((0.0, 0.0, 1.0)),
+            Vector3::from((0.0, 0.0, -1.0)),
+        ));
+        let shaded = material.shade(&ray, &hit_record());
+        let expected = Vector3::from((0.0, half, half));
+        assert!((shaded.norm - expected).norm() < 1e-5);
+    }
+}
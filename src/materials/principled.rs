@@ -0,0 +1,407 @@
+use std::f32::consts::PI;
+
+use crate::{
+    color::Color,
+    geometry::axis::Axes3D,
+    objects::hittable::HitRecord,
+    optical::{
+        microfacet::Ggx,
+        ray::Ray,
+        scatter::{fresnel_dielectric, reflect, refract},
+    },
+    renderers::adaptive::luminance,
+    samplers::{
+        sampler::{sample_1d, sample_2d},
+        warp::cosine_hemisphere,
+    },
+    vectors::{ops::MatrixDot, vector3::Vector3},
+};
+
+use super::material::{Material, Scatter};
+
+/// Principled BSDF after Burley, "Physically Based Shading at Disney" (2012)
+/// and "Extending the Disney BRDF to a BSDF with Integrated Subsurface
+/// Scattering" (2015).
+///
+/// All sliders are in [0, 1] except `index_of_refraction`.
+pub struct Principled {
+    pub base_color: Color,
+    pub metallic: f32,
+    pub roughness: f32,
+    /// Strength of the dielectric specular reflection, 0.5 corresponds to
+    /// 4% reflectance at normal incidence.
+    pub specular: f32,
+    pub specular_tint: f32,
+    pub sheen: f32,
+    pub sheen_tint: f32,
+    pub clearcoat: f32,
+    pub clearcoat_gloss: f32,
+    pub transmission: f32,
+    pub index_of_refraction: f32,
+}
+
+impl Default for Principled {
+    fn default() -> Self {
+        Principled {
+            base_color: Color::from((0.8, 0.8, 0.8)),
+            metallic: 0.0,
+            roughness: 0.5,
+            specular: 0.5,
+            specular_tint: 0.0,
+            sheen: 0.0,
+            sheen_tint: 0.5,
+            clearcoat: 0.0,
+            clearcoat_gloss: 1.0,
+            transmission: 0.0,
+            index_of_refraction: 1.5,
+        }
+    }
+}
+
+/// Weights of each lobe, also used as their sampling probabilities.
+struct Lobes {
+    diffuse: f32,
+    specular: f32,
+    clearcoat: f32,
+    glass: f32,
+}
+
+impl Lobes {
+    fn total(&self) -> f32 {
+        self.diffuse + self.specular + self.clearcoat + self.glass
+    }
+}
+
+fn lerp(a: Color, b: Color, t: f32) -> Color {
+    a * (1.0 - t) + b * t
+}
+
+fn schlick_weight(cosine: f32) -> f32 {
+    (1.0 - cosine).clamp(0.0, 1.0).powi(5)
+}
+
+impl Principled {
+    fn lobes(&self, front_face: bool) -> Lobes {
+        let glass = (1.0 - self.metallic) * self.transmission;
+        if !front_face && glass > 0.0 {
+            // Only rays transmitted into the object can hit a back face of
+            // glass. Opaque back faces (planes, open meshes) shade like
+            // front faces.
+            return Lobes {
+                diffuse: 0.0,
+                specular: 0.0,
+                clearcoat: 0.0,
+                glass: 1.0,
+            };
+        }
+        Lobes {
+            diffuse: (1.0 - self.metallic) * (1.0 - self.transmission),
+            specular: 1.0 - glass,
+            clearcoat: 0.25 * self.clearcoat,
+            glass,
+        }
+    }
+
+    /// Ratio of the index of refraction of the outgoing side over the
+    /// incoming (transmitted) side.
+    fn eta_o_over_i(&self, front_face: bool) -> f32 {
+        if front_face {
+            1.0 / self.index_of_refraction
+        } else {
+            self.index_of_refraction
+        }
+    }
+
+    fn tint(&self) -> Color {
+        let lum = luminance(&self.base_color);
+        if lum > 0.0 {
+            self.base_color / lum
+        } else {
+            Color::from((1.0, 1.0, 1.0))
+        }
+    }
+
+    fn specular_ggx(&self) -> Ggx {
+        Ggx::from_roughness(self.roughness)
+    }
+
+    fn clearcoat_ggx(&self) -> Ggx {
+        Ggx {
+            alpha: 0.1 * (1.0 - self.clearcoat_gloss) + 0.001 * self.clearcoat_gloss,
+        }
+    }
+
+    /// Generalized half vector between `wo` and `wi`, on the side of +z.
+    fn half_vector(&self, wo: &Vector3, wi: &Vector3, front_face: bool) -> Option<Vector3> {
+        let h = if wi.z > 0.0 {
+            *wo + *wi
+        } else {
+            *wo * self.eta_o_over_i(front_face) + *wi
+        };
+        if h.norm_squared() == 0.0 {
+            return None;
+        }
+        let h = h.to_unit();
+        Some(if h.z < 0.0 { -h } else { h })
+    }
+
+    /// BSDF times |cos(wi)| in the local shading frame.
+    fn eval_local(&self, wo: &Vector3, wi: &Vector3, front_face: bool) -> Color {
+        let lobes = self.lobes(front_face);
+        let Some(h) = self.half_vector(wo, wi, front_face) else {
+            return Color::zero();
+        };
+        if wo.z <= 0.0 || wi.z == 0.0 {
+            return Color::zero();
+        }
+        let cos_o = wo.z;
+        let cos_i = wi.z.abs();
+        let cos_oh = wo.dot(&h);
+        let cos_ih = wi.dot(&h);
+        if cos_oh <= 0.0 {
+            return Color::zero();
+        }
+        let eta = self.eta_o_over_i(front_face);
+        let fresnel_glass = fresnel_dielectric(cos_oh, eta);
+        let white = Color::from((1.0, 1.0, 1.0));
+
+        let mut f = Color::zero();
+        if wi.z > 0.0 {
+            // diffuse with retro-reflection and sheen
+            if lobes.diffuse > 0.0 {
+                let fd90 = 0.5 + 2.0 * self.roughness * cos_ih.powi(2);
+                let fl = schlick_weight(cos_i);
+                let fv = schlick_weight(cos_o);
+                let retro = (1.0 + (fd90 - 1.0) * fl) * (1.0 + (fd90 - 1.0) * fv);
+                let sheen = lerp(white, self.tint(), self.sheen_tint)
+                    * (self.sheen * schlick_weight(cos_ih));
+                f += (self.base_color * (retro / PI) + sheen) * lobes.diffuse;
+            }
+            if lobes.specular > 0.0 {
+                let ggx = self.specular_ggx();
+                let dielectric_f0 =
+                    lerp(white, self.tint(), self.specular_tint) * (self.specular * 0.08);
+                let f0 = lerp(dielectric_f0, self.base_color, self.metallic);
+                let fresnel = f0 + (white - f0) * schlick_weight(cos_ih);
+                f +=
+                    fresnel * (ggx.d(&h) * ggx.g2(wo, wi) / (4.0 * cos_o * cos_i) * lobes.specular);
+            }
+            if lobes.clearcoat > 0.0 {
+                let ggx = self.clearcoat_ggx();
+                let fresnel = 0.04 + 0.96 * schlick_weight(cos_ih);
+                f += white
+                    * (fresnel * ggx.d(&h) * ggx.g2(wo, wi) / (4.0 * cos_o * cos_i)
+                        * lobes.clearcoat);
+            }
+            if lobes.glass > 0.0 {
+                let ggx = self.specular_ggx();
+                f += white
+                    * (fresnel_glass * ggx.d(&h) * ggx.g2(wo, wi) / (4.0 * cos_o * cos_i)
+                        * lobes.glass);
+            }
+        } else if lobes.glass > 0.0 && cos_ih < 0.0 {
+            // rough dielectric transmission (Walter et al. 2007)
+            let ggx = self.specular_ggx();
+            let denominator = (eta * cos_oh + cos_ih).powi(2);
+            let transmittance =
+                (1.0 - fresnel_glass) * ggx.d(&h) * ggx.g2(wo, wi) * cos_ih.abs() * cos_oh
+                    / (cos_o * cos_i * denominator);
+            f += self.base_color * (transmittance * lobes.glass);
+        }
+        f * cos_i
+    }
+
+    /// Probability density of `sample_local` producing `wi`.
+    fn pdf_local(&self, wo: &Vector3, wi: &Vector3, front_face: bool) -> f32 {
+        let lobes = self.lobes(front_face);
+        let total = lobes.total();
+        let Some(h) = self.half_vector(wo, wi, front_face) else {
+            return 0.0;
+        };
+        if total <= 0.0 || wo.z <= 0.0 {
+            return 0.0;
+        }
+        let cos_oh = wo.dot(&h);
+        let cos_ih = wi.dot(&h);
+        if cos_oh <= 0.0 {
+            return 0.0;
+        }
+        let eta = self.eta_o_over_i(front_face);
+        let fresnel_glass = fresnel_dielectric(cos_oh, eta);
+        let specular = self.specular_ggx();
+
+        let mut pdf = 0.0;
+        if wi.z > 0.0 {
+            let reflection = specular.visible_d(wo, &h) / (4.0 * cos_oh);
+            pdf += lobes.diffuse * wi.z / PI;
+            pdf += lobes.specular * reflection;
+            pdf += lobes.clearcoat * self.clearcoat_ggx().visible_d(wo, &h) / (4.0 * cos_oh);
+            pdf += lobes.glass * fresnel_glass * reflection;
+        } else if cos_ih < 0.0 {
+            let jacobian = cos_ih.abs() / (eta * cos_oh + cos_ih).powi(2);
+            pdf += lobes.glass * (1.0 - fresnel_glass) * specular.visible_d(wo, &h) * jacobian;
+        }
+        pdf / total
+    }
+
+    /// Sample a scattered direction by picking one lobe.
+    fn sample_local(&self, wo: &Vector3, front_face: bool) -> Option<Vector3> {
+        let lobes = self.lobes(front_face);
+        let total = lobes.total();
+        if total <= 0.0 {
+            return None;
+        }
+        let u = sample_1d() * total;
+        let (wi, transmitted) = if u < lobes.diffuse {
+            (cosine_hemisphere(sample_2d()), false)
+        } else if u < lobes.diffuse + lobes.specular {
+            let (u1, u2) = sample_2d();
+            let m = self.specular_ggx().sample_visible_normal(wo, u1, u2);
+            (reflect(&-*wo, &m), false)
+        } else if u < lobes.diffuse + lobes.specular + lobes.clearcoat {
+            let (u1, u2) = sample_2d();
+            let m = self.clearcoat_ggx().sample_visible_normal(wo, u1, u2);
+            (reflect(&-*wo, &m), false)
+        } else {
+            let (u1, u2) = sample_2d();
+            let m = self.specular_ggx().sample_visible_normal(wo, u1, u2);
+            let eta = self.eta_o_over_i(front_face);
+            if sample_1d() < fresnel_dielectric(wo.dot(&m), eta) {
+                (reflect(&-*wo, &m), false)
+            } else {
+                match refract(&-*wo, &m, eta) {
+                    Some(wi) => (wi, true),
+                    None => (reflect(&-*wo, &m), false),
+                }
+            }
+        };
+        // Discard samples scattered into the wrong hemisphere of the macro
+        // surface, since `pdf_local` does not account for them.
+        if wi.z == 0.0 || (wi.z < 0.0) != transmitted {
+            return None;
+        }
+        Some(wi)
+    }
+}
+
+impl Material for Principled {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<Scatter> {
+        let frame = Axes3D::around(hit_record.norm);
+        let wo = frame.world_to_local(&-ray.direction.to_unit());
+        let wi = self.sample_local(&wo, hit_record.front_face)?;
+
+        // Weight by the pdf of the whole mixture of lobes, rather than only
+        // the one sampled, to keep the variance low (one-sample MIS).
+        let pdf = self.pdf_local(&wo, &wi, hit_record.front_face);
+        if pdf <= 0.0 {
+            return None;
+        }
+        let attenuation = self.eval_local(&wo, &wi, hit_record.front_face) / pdf;
+        Some(Scatter {
+            attenuation,
+            ray: Ray {
+                origin: hit_record.point,
+                direction: frame.local_to_world(&wi),
+            },
+        })
+    }
+
+    fn eval(&self, ray: &Ray, hit_record: &HitRecord, direction: &Vector3) -> Color {
+        let frame = Axes3D::around(hit_record.norm);
+        let wo = frame.world_to_local(&-ray.direction.to_unit());
+        let wi = frame.world_to_local(&direction.to_unit());
+        self.eval_local(&wo, &wi, hit_record.front_face)
+    }
+
+    fn pdf(&self, ray: &Ray, hit_record: &HitRecord, direction: &Vector3) -> f32 {
+        let frame = Axes3D::around(hit_record.norm);
+        let wo = frame.world_to_local(&-ray.direction.to_unit());
+        let wi = frame.world_to_local(&direction.to_unit());
+        self.pdf_local(&wo, &wi, hit_record.front_face)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::samplers::{
+        sampler::{with_sampler, Sampler},
+        sobol::SobolSampler,
+        warp::unit_sphere,
+    };
+
+    /// Run `f` drawing the sample `index` of a seeded sampler.
+    fn with_sample<R>(index: u32, f: impl FnOnce() -> R) -> R {
+        let mut sampler = SobolSampler::new(7);
+        sampler.start_pixel_sample((0, 0), index);
+        with_sampler(Box::new(sampler), f)
+    }
+
+    #[test]
+    fn should_sample_consistently_with_eval_and_pdf() {
+        let material = Principled {
+            base_color: Color::from((0.9, 0.6, 0.3)),
+            metallic: 0.3,
+            roughness: 0.6,
+            sheen: 0.5,
+            clearcoat: 0.5,
+            // keep the lobes wide enough for the uniform estimate to converge
+            clearcoat_gloss: 0.0,
+            transmission: 0.5,
+            ..Default::default()
+        };
+        let wo = Vector3::from((0.5, 0.0, 0.8)).to_unit();
+        let samples = 1 << 14;
+
+        // albedo estimated with importance sampling...
+        let mut sampled = Color::zero();
+        let mut pdf_integral = 0.0;
+        // ...and with uniform sampling
+        let mut uniform = Color::zero();
+        for i in 0..samples {
+            with_sample(i, || {
+                if let Some(wi) = material.sample_local(&wo, true) {
+                    let pdf = material.pdf_local(&wo, &wi, true);
+                    if pdf > 0.0 {
+                        sampled += material.eval_local(&wo, &wi, true) / pdf;
+                    }
+                }
+                let wi = unit_sphere(sample_2d());
+                pdf_integral += material.pdf_local(&wo, &wi, true) * 4.0 * PI;
+                uniform += material.eval_local(&wo, &wi, true) * 4.0 * PI;
+            });
+        }
+        sampled /= samples as f32;
+        pdf_integral /= samples as f32;
+        uniform /= samples as f32;
+
+        // some samples leak below the surface and are discarded
+        assert!(
+            pdf_integral > 0.8 && pdf_integral < 1.05,
+            "{}",
+            pdf_integral
+        );
+        assert!(
+            (sampled - uniform).norm() < 0.05,
+            "{} {}",
+            sampled.x,
+            uniform.x
+        );
+        assert!(sampled.x <= 1.0 && sampled.y <= 1.0 && sampled.z <= 1.0);
+    }
+
+    #[test]
+    fn should_not_refract_opaque_back_faces() {
+        let material = Principled {
+            base_color: Color::from((0.8, 0.8, 0.8)),
+            roughness: 0.5,
+            ..Default::default()
+        };
+        let wo = Vector3::from((0.3, 0.0, 0.9)).to_unit();
+        for i in 0..1024 {
+            if let Some(wi) = with_sample(i, || material.sample_local(&wo, false)) {
+                assert!(wi.z > 0.0);
+            }
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use crate::{
+    color::Color,
+    geometry::axis::Axes3D,
+    objects::hittable::HitRecord,
+    optical::{
+        microfacet::Ggx,
+        ray::Ray,
+        scatter::{beer_lambert, fresnel_dielectric, reflect, refract},
+    },
+    samplers::sampler::{sample_1d, sample_2d},
+    vectors::ops::MatrixDot,
+};
+
+use super::material::{Material, Scatter};
+
+/// Frosted glass: GGX microfacet reflection and transmission (Walter et al.
+/// 2007) with Beer-Lambert absorption inside of the object.
+pub struct RoughDielectric {
+    pub index_of_refraction: f32,
+    /// Perceptual roughness in [0, 1].
+    pub roughness: f32,
+    /// Absorption coefficient per unit length inside of the object.
+    pub absorption: Color,
+}
+
+impl Material for RoughDielectric {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<Scatter> {
+        let refraction_ratio = if hit_record.front_face {
+            1.0 / self.index_of_refraction
+        } else {
+            self.index_of_refraction
+        };
+
+        let unit_direction = ray.direction.to_unit();
+        let frame = Axes3D::around(hit_record.norm);
+        let wo = frame.world_to_local(&-unit_direction);
+        if wo.z <= 0.0 {
+            return None;
+        }
+
+        let ggx = Ggx::from_roughness(self.roughness);
+        let (u1, u2) = sample_2d();
+        let m = ggx.sample_visible_normal(&wo, u1, u2);
+        let fresnel = fresnel_dielectric(wo.dot(&m), refraction_ratio);
+
+        // Choose reflection or transmission by the Fresnel term, so the
+        // weight f * cos / pdf reduces to G2 / G1 for both.
+        let wi = if sample_1d() < fresnel {
+            reflect(&-wo, &m)
+        } else {
+            refract(&-wo, &m, refraction_ratio).unwrap_or_else(|| reflect(&-wo, &m))
+        };
+        let reflected = wi.z > 0.0;
+        if reflected != (wi.dot(&m) > 0.0) {
+            // scattered into the wrong hemisphere of the macro surface
+            return None;
+        }
+
+        let mut attenuation = Color::from((1.0, 1.0, 1.0)) * (ggx.g2(&wo, &wi) / ggx.g1(&wo));
+        if !hit_record.front_face {
+            attenuation *= beer_lambert(self.absorption, hit_record.t * ray.direction.norm());
+        }
+        Some(Scatter {
+            attenuation,
+            ray: Ray {
+                origin: hit_record.point,
+                direction: frame.local_to_world(&wi),
+            },
+        })
+    }
+
+    fn is_specular(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        materials::dielectric::DiElectric,
+        vectors::vector3::{Point3, Vector3},
+    };
+    use uuid::Uuid;
+
+    /// Mean attenuation and fraction of reflected rays over many scatters.
+    fn scatter_statistics(
+        material: &dyn Material,
+        ray: &Ray,
+        hit_record: &HitRecord,
+    ) -> (f32, f32) {
+        let samples = 20000;
+        let mut energy = 0.0;
+        let mut reflected = 0;
+        for _ in 0..samples {
+            if let Some(scattered) = material.scatter(ray, hit_record) {
+                energy += scattered.attenuation.x;
+                if scattered.ray.direction.z > 0.0 {
+                    reflected += 1;
+                }
+            }
+        }
+        (energy / samples as f32, reflected as f32 / samples as f32)
+    }
+
+    #[test]
+    fn should_match_smooth_dielectric_without_roughness() {
+        let hit_record = HitRecord {
+            point: Point3::zero(),
+            norm: Vector3::from((0.0, 0.0, 1.0)),
+            front_face: true,
+            t: 1.0,
+            material_id: Uuid::nil(),
+            uv: (0.0, 0.0),
+            tangent: Vector3::from((1.0, 0.0, 0.0)),
+            bitangent: Vector3::from((0.0, 1.0, 0.0)),
+            uv_lengths: (1.0, 1.0),
+        };
+        let ray = Ray {
+            origin: Point3::from((-1.0, 0.0, 1.0)),
+            direction: Vector3::from((1.0, 0.0, -1.0)),
+        };
+        let rough = RoughDielectric {
+            index_of_refraction: 1.5,
+            roughness: 0.0,
+            absorption: Color::zero(),
+        };
+        let smooth = DiElectric {
+            index_of_refraction: 1.5,
+            absorption: Color::zero(),
+            thin_film: None,
+        };
+
+        let (rough_energy, rough_reflected) = scatter_statistics(&rough, &ray, &hit_record);
+        let (smooth_energy, smooth_reflected) = scatter_statistics(&smooth, &ray, &hit_record);
+        assert!(
+            (rough_energy - smooth_energy).abs() < 0.01,
+            "{} != {}",
+            rough_energy,
+            smooth_energy
+        );
+        // the smooth dielectric uses Schlick's approximation of the exact
+        // Fresnel term of the rough one
+        assert!(
+            (rough_reflected - smooth_reflected).abs() < 0.02,
+            "{} != {}",
+            rough_reflected,
+            smooth_reflected
+        );
+    }
+}
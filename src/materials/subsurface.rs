@@ -0,0 +1,183 @@
+use crate::{
+    color::Color,
+    objects::hittable::HitRecord,
+    optical::{
+        ray::Ray,
+        scatter::{fresnel_dielectric, reflect, refract},
+    },
+    samplers::{
+        sampler::{sample_1d, sample_2d},
+        warp::unit_sphere,
+    },
+    vectors::{ops::MatrixDot, vector3::Vector3},
+};
+
+use super::material::{Material, Scatter};
+
+/// Random walk subsurface scattering (skin, wax, marble).
+///
+/// Light refracts into the object through a smooth dielectric interface and
+/// random-walks through a homogeneous medium until it leaves through the
+/// boundary of the object again. The walk is driven by the rays hitting the
+/// inside of the boundary: before reaching it, a free-flight distance is
+/// sampled, and if it is shorter the ray scatters inside instead.
+/// So the object must be closed.
+pub struct Subsurface {
+    /// Color of the surface after multiple scattering.
+    pub albedo: Color,
+    /// Average distance light travels inside before scattering, per channel.
+    pub mean_free_path: Color,
+    pub index_of_refraction: f32,
+}
+
+/// Map the multiple scattering albedo to the single scattering albedo
+/// (Chiang et al. 2016, "Practical and Controllable Subsurface Scattering
+/// for Production Path Tracing").
+fn single_scattering_albedo(albedo: f32) -> f32 {
+    let a = albedo.clamp(0.0, 1.0);
+    1.0 - (4.09712 + 4.20863 * a - (9.59217 + 41.6808 * a + 17.7126 * a * a).sqrt()).powi(2)
+}
+
+fn transmittance(extinction: &Color, distance: f32) -> Color {
+    Color::from((
+        (-extinction.x * distance).exp(),
+        (-extinction.y * distance).exp(),
+        (-extinction.z * distance).exp(),
+    ))
+}
+
+fn average(color: &Color) -> f32 {
+    (color.x + color.y + color.z) / 3.0
+}
+
+impl Subsurface {
+    fn extinction(&self) -> Color {
+        Color::from((1.0, 1.0, 1.0)) / self.mean_free_path
+    }
+
+    /// Reflect or refract at the interface, choosing by the Fresnel term.
+    fn interface(&self, unit_direction: &Vector3, hit_record: &HitRecord) -> Vector3 {
+        let refraction_ratio = if hit_record.front_face {
+            1.0 / self.index_of_refraction
+        } else {
+            self.index_of_refraction
+        };
+        let cos_theta = -unit_direction.dot(&hit_record.norm);
+        if sample_1d() < fresnel_dielectric(cos_theta, refraction_ratio) {
+            reflect(unit_direction, &hit_record.norm)
+        } else {
+            refract(unit_direction, &hit_record.norm, refraction_ratio)
+                .unwrap_or_else(|| reflect(unit_direction, &hit_record.norm))
+        }
+    }
+}
+
+impl Material for Subsurface {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<Scatter> {
+        let unit_direction = ray.direction.to_unit();
+        let white = Color::from((1.0, 1.0, 1.0));
+        if hit_record.front_face {
+            return Some(Scatter {
+                attenuation: white,
+                ray: Ray {
+                    origin: hit_record.point,
+                    direction: self.interface(&unit_direction, hit_record),
+                },
+            });
+        }
+
+        // Sample a free-flight distance with a randomly chosen channel, and
+        // weight by the pdf averaged over all channels (spectral MIS).
+        let extinction = self.extinction();
+        let channel = ((sample_1d() * 3.0) as usize).min(2);
+        let distance = -(1.0 - sample_1d()).ln() / extinction[channel];
+        let boundary_distance = hit_record.t * ray.direction.norm();
+
+        if distance < boundary_distance {
+            // scatter inside of the medium
+            let scattering = Color::from((
+                single_scattering_albedo(self.albedo.x),
+                single_scattering_albedo(self.albedo.y),
+                single_scattering_albedo(self.albedo.z),
+            )) * extinction;
+            let t = transmittance(&extinction, distance);
+            let pdf = average(&(extinction * t));
+            Some(Scatter {
+                attenuation: scattering * t / pdf,
+                ray: Ray {
+                    origin: ray.origin + unit_direction * distance,
+                    direction: unit_sphere(sample_2d()),
+                },
+            })
+        } else {
+            // reached the boundary
+            let t = transmittance(&extinction, boundary_distance);
+            Some(Scatter {
+                attenuation: t / average(&t),
+                ray: Ray {
+                    origin: hit_record.point,
+                    direction: self.interface(&unit_direction, hit_record),
+                },
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vectors::vector3::Point3;
+    use uuid::Uuid;
+
+    #[test]
+    fn should_map_albedo_bounds_to_themselves() {
+        assert!(single_scattering_albedo(0.0).abs() < 1.0e-4);
+        assert!((single_scattering_albedo(1.0) - 1.0).abs() < 1.0e-4);
+        assert!(single_scattering_albedo(0.5) > 0.5);
+    }
+
+    #[test]
+    fn should_reach_the_boundary_with_the_transmittance_of_each_channel() {
+        let material = Subsurface {
+            albedo: Color::from((0.8, 0.8, 0.8)),
+            mean_free_path: Color::from((0.5, 1.0, 2.0)),
+            index_of_refraction: 1.0,
+        };
+        // inside of the object, one unit away from its boundary
+        let hit_record = HitRecord {
+            point: Point3::from((0.0, 0.0, 1.0)),
+            norm: Vector3::from((0.0, 0.0, -1.0)),
+            front_face: false,
+            t: 1.0,
+            material_id: Uuid::nil(),
+            uv: (0.0, 0.0),
+            tangent: Vector3::from((1.0, 0.0, 0.0)),
+            bitangent: Vector3::from((0.0, 1.0, 0.0)),
+            uv_lengths: (1.0, 1.0),
+        };
+        let ray = Ray {
+            origin: Point3::zero(),
+            direction: Vector3::from((0.0, 0.0, 1.0)),
+        };
+
+        let samples = 50000;
+        let mut transmitted = Color::zero();
+        for _ in 0..samples {
+            let scattered = material.scatter(&ray, &hit_record).unwrap();
+            if scattered.ray.origin.z == hit_record.point.z {
+                transmitted += scattered.attenuation;
+            }
+        }
+        transmitted /= samples as f32;
+        for channel in 0..3 {
+            let expected = (-1.0 / material.mean_free_path[channel]).exp();
+            let actual = transmitted[channel];
+            assert!(
+                (actual - expected).abs() < 0.02,
+                "{} != {}",
+                actual,
+                expected
+            );
+        }
+    }
+}
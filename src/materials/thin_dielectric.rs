@@ -0,0 +1,112 @@
+use crate::{
+    color::Color,
+    objects::hittable::HitRecord,
+    optical::{
+        ray::Ray,
+        scatter::{fresnel_dielectric, reflect},
+    },
+    samplers::sampler::sample_1d,
+    vectors::ops::MatrixDot,
+};
+
+use super::material::{Material, Scatter};
+
+/// Infinitely thin glass sheet (e.g. a window modeled as a `Plane`).
+/// Light is either reflected or passes straight through without refraction,
+/// accounting for the inter-reflections between both faces of the sheet.
+pub struct ThinDielectric {
+    pub index_of_refraction: f32,
+    /// Color of transmitted light.
+    pub tint: Color,
+}
+
+impl Material for ThinDielectric {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<Scatter> {
+        let unit_direction = ray.direction.to_unit();
+        let cos_theta = -unit_direction.dot(&hit_record.norm);
+        let r = fresnel_dielectric(cos_theta, 1.0 / self.index_of_refraction);
+        // sum of the geometric series of internal reflections
+        let reflectance = if r < 1.0 { 2.0 * r / (1.0 + r) } else { 1.0 };
+
+        let (direction, attenuation) = if sample_1d() < reflectance {
+            (
+                reflect(&unit_direction, &hit_record.norm),
+                Color::from((1.0, 1.0, 1.0)),
+            )
+        } else {
+            (unit_direction, self.tint)
+        };
+        Some(Scatter {
+            attenuation,
+            ray: Ray {
+                origin: hit_record.point,
+                direction,
+            },
+        })
+    }
+
+    fn is_specular(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        optical::scatter::fresnel_dielectric,
+        vectors::vector3::{Point3, Vector3},
+    };
+    use uuid::Uuid;
+
+    #[test]
+    fn should_conserve_reflected_and_transmitted_energy() {
+        let hit_record = HitRecord {
+            point: Point3::zero(),
+            norm: Vector3::from((0.0, 0.0, 1.0)),
+            front_face: true,
+            t: 1.0,
+            material_id: Uuid::nil(),
+            uv: (0.0, 0.0),
+            tangent: Vector3::from((1.0, 0.0, 0.0)),
+            bitangent: Vector3::from((0.0, 1.0, 0.0)),
+            uv_lengths: (1.0, 1.0),
+        };
+        let direction = Vector3::from((1.0, 0.0, -1.0));
+        let ray = Ray {
+            origin: Point3::from((-1.0, 0.0, 1.0)),
+            direction,
+        };
+        let clear = ThinDielectric {
+            index_of_refraction: 1.5,
+            tint: Color::from((1.0, 1.0, 1.0)),
+        };
+
+        let samples = 20000;
+        let (mut reflected, mut transmitted) = (0, 0);
+        for _ in 0..samples {
+            let scattered = clear.scatter(&ray, &hit_record).unwrap();
+            assert_eq!(scattered.attenuation.x, 1.0);
+            if scattered.ray.direction.z > 0.0 {
+                reflected += 1;
+            } else {
+                // passes straight through the sheet
+                assert!((scattered.ray.direction - direction.to_unit()).norm() < 1e-6);
+                transmitted += 1;
+            }
+        }
+        assert_eq!(reflected + transmitted, samples);
+
+        // both faces reflect r, and the light bouncing between them adds up
+        // to 2r / (1 + r)
+        let r = fresnel_dielectric(1.0 / 2.0f32.sqrt(), 1.0 / 1.5);
+        let expected = 2.0 * r / (1.0 + r);
+        let reflected = reflected as f32 / samples as f32;
+        assert!(
+            (reflected - expected).abs() < 0.01,
+            "{} != {}",
+            reflected,
+            expected
+        );
+    }
+}
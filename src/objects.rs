@@ -1,6 +1,10 @@
+pub mod constant_medium;
+pub mod heterogeneous_medium;
 pub mod hittable;
 pub mod hittables;
 pub mod object;
+pub mod opacity;
 pub mod plane;
+pub mod sdf_shape;
 pub mod sphere;
 pub mod triangle;
@@ -0,0 +1,91 @@
+use uuid::Uuid;
+
+use crate::{
+    geometry::axis::Axes3D, interval::Interval, optical::ray::Ray, samplers::sampler::sample_1d,
+};
+
+use super::hittable::{HitRecord, Hittable};
+
+/// Volume of constant density bounded by a closed shape (fog, smoke, ...).
+/// A ray travelling through the volume scatters with probability
+/// proportional to the distance travelled inside of it.
+/// https://raytracing.github.io/books/RayTracingTheNextWeek.html#volumes
+pub struct ConstantMedium {
+    pub boundary: Box<dyn Hittable + Send>,
+    pub density: f32,
+    /// Should refer to a phase function material such as `Isotropic`.
+    pub material_id: Uuid,
+}
+
+impl Hittable for ConstantMedium {
+    fn hit(&self, ray: &Ray, interval: Interval<f32>) -> Option<HitRecord> {
+        // Find where the ray enters and leaves the boundary, even when it
+        // originates inside of the volume.
+        let enter = self.boundary.hit(ray, Interval::universe())?;
+        let leave = self
+            .boundary
+            .hit(ray, Interval::from((enter.t + 0.0001, f32::INFINITY)))?;
+
+        let t_enter = enter.t.max(interval.min).max(0.0);
+        let t_leave = leave.t.min(interval.max);
+        if t_enter >= t_leave {
+            return None;
+        }
+
+        let ray_length = ray.direction.norm();
+        let distance_inside = (t_leave - t_enter) * ray_length;
+        let hit_distance = -(1.0 - sample_1d()).ln() / self.density;
+        if hit_distance > distance_inside {
+            return None;
+        }
+
+        let t = t_enter + hit_distance / ray_length;
+        let frame = Axes3D::around(-ray.direction.to_unit());
+        Some(HitRecord {
+            point: ray.at(t),
+            // Normal and face are meaningless inside of a volume.
+            norm: frame.w,
+            front_face: true,
+            t,
+            material_id: self.material_id,
+            uv: (0.0, 0.0),
+            tangent: frame.u,
+            bitangent: frame.v,
+            uv_lengths: (1.0, 1.0),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{objects::sphere::Sphere, vectors::vector3::Point3};
+
+    #[test]
+    fn should_scatter_from_inside_with_beer_lambert_probability() {
+        let medium = ConstantMedium {
+            boundary: Box::new(Sphere {
+                r: 1.0,
+                center: Point3::zero(),
+                material_id: Uuid::nil(),
+            }),
+            density: 0.7,
+            material_id: Uuid::nil(),
+        };
+        // starts at the center, with a direction that is not a unit vector
+        let ray = Ray::from((Point3::zero(), Point3::from((0.0, 2.0, 0.0))));
+
+        let samples = 20000;
+        let mut escaped = 0;
+        for _ in 0..samples {
+            match medium.hit(&ray, Interval::from((0.0, f32::INFINITY))) {
+                Some(hit_record) => assert!(hit_record.point.norm() <= 1.0 + 1.0e-4),
+                None => escaped += 1,
+            }
+        }
+        let mean = escaped as f32 / samples as f32;
+        // Beer-Lambert law over the distance of 1 to the boundary
+        let expected = (-0.7f32).exp();
+        assert!((mean - expected).abs() < 0.02, "{} != {}", mean, expected);
+    }
+}
@@ -0,0 +1,113 @@
+use uuid::Uuid;
+
+use crate::{
+    geometry::axis::Axes3D, interval::Interval, optical::ray::Ray, samplers::sampler::sample_1d,
+    volumes::density::DensityField,
+};
+
+use super::hittable::{HitRecord, Hittable};
+
+/// Volume with spatially varying density (clouds, smoke, fire, ...) inside of
+/// a closed boundary. Collisions are sampled with delta tracking against the
+/// majorant `density.max_density() * density_scale`.
+pub struct HeterogeneousMedium {
+    pub boundary: Box<dyn Hittable + Send>,
+    pub density: Box<dyn DensityField + Send>,
+    pub density_scale: f32,
+    /// Should refer to a phase function material such as `HenyeyGreenstein`.
+    pub material_id: Uuid,
+}
+
+impl HeterogeneousMedium {
+    /// Return the part of the ray inside of the boundary clamped to `interval`.
+    fn inside_interval(&self, ray: &Ray, interval: Interval<f32>) -> Option<Interval<f32>> {
+        let enter = self.boundary.hit(ray, Interval::universe())?;
+        let leave = self
+            .boundary
+            .hit(ray, Interval::from((enter.t + 0.0001, f32::INFINITY)))?;
+
+        let t_enter = enter.t.max(interval.min).max(0.0);
+        let t_leave = leave.t.min(interval.max);
+        if t_enter >= t_leave {
+            None
+        } else {
+            Some(Interval::from((t_enter, t_leave)))
+        }
+    }
+}
+
+impl Hittable for HeterogeneousMedium {
+    fn hit(&self, ray: &Ray, interval: Interval<f32>) -> Option<HitRecord> {
+        let inside = self.inside_interval(ray, interval)?;
+        let majorant = self.density.max_density() * self.density_scale;
+        if majorant <= 0.0 {
+            return None;
+        }
+
+        // Delta tracking: sample tentative collisions against the majorant
+        // and accept them with probability density / majorant.
+        let ray_length = ray.direction.norm();
+        let mut t = inside.min;
+        loop {
+            t -= (1.0 - sample_1d()).ln() / (majorant * ray_length);
+            if t >= inside.max {
+                return None;
+            }
+            let point = ray.at(t);
+            let density = self.density.density(point) * self.density_scale;
+            if sample_1d() * majorant < density {
+                let frame = Axes3D::around(-ray.direction.to_unit());
+                return Some(HitRecord {
+                    point,
+                    norm: frame.w,
+                    front_face: true,
+                    t,
+                    material_id: self.material_id,
+                    uv: (0.0, 0.0),
+                    tangent: frame.u,
+                    bitangent: frame.v,
+                    uv_lengths: (1.0, 1.0),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        objects::sphere::Sphere, vectors::vector3::Point3, volumes::density::ConstantDensity,
+    };
+
+    #[test]
+    fn should_pass_through_homogeneous_medium_with_beer_lambert_probability() {
+        let medium = HeterogeneousMedium {
+            boundary: Box::new(Sphere {
+                r: 1.0,
+                center: Point3::zero(),
+                material_id: Uuid::nil(),
+            }),
+            density: Box::new(ConstantDensity { density: 0.5 }),
+            density_scale: 1.0,
+            material_id: Uuid::nil(),
+        };
+        let ray = Ray::from((
+            Point3::from((-2.0, 0.0, 0.0)),
+            Point3::from((1.0, 0.0, 0.0)),
+        ));
+
+        let samples = 20000;
+        let escaped = (0..samples)
+            .filter(|_| {
+                medium
+                    .hit(&ray, Interval::from((0.0, f32::INFINITY)))
+                    .is_none()
+            })
+            .count();
+        let mean = escaped as f32 / samples as f32;
+        // Beer-Lambert law over a chord of length 2
+        let expected = (-0.5f32 * 2.0).exp();
+        assert!((mean - expected).abs() < 0.02, "{} != {}", mean, expected);
+    }
+}
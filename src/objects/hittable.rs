@@ -1,6 +1,11 @@
 use uuid::Uuid;
 
-use crate::{interval::Interval, optical::ray::Ray, Point3, Vector3};
+use crate::{
+    interval::Interval,
+    optical::ray::Ray,
+    renderers::statistics::short_type_name,
+    vectors::vector3::{Point3, Vector3},
+};
 
 pub struct HitRecord {
     pub point: Point3,
@@ -8,8 +13,22 @@ pub struct HitRecord {
     pub front_face: bool,
     pub t: f32,
     pub material_id: Uuid,
+    /// Surface coordinates for texture lookups.
+    pub uv: (f32, f32),
+    /// Unit tangent along increasing u (dp/du).
+    pub tangent: Vector3,
+    /// Unit tangent along increasing v (dp/dv).
+    pub bitangent: Vector3,
+    /// Lengths |dp/du| and |dp/dv|: distance moved on the surface per unit
+    /// of u and v.
+    pub uv_lengths: (f32, f32),
 }
 
 pub trait Hittable: Sync {
     fn hit(&self, ray: &Ray, interval: Interval<f32>) -> Option<HitRecord>;
+
+    /// Name of the primitive in the render statistics.
+    fn primitive_name(&self) -> &'static str {
+        short_type_name(std::any::type_name::<Self>())
+    }
 }
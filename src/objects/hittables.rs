@@ -20,24 +20,30 @@ impl<I: Sized> Hittables<I> {
         }
     }
 }
+impl<I: Sized> Default for Hittables<I> {
+    fn default() -> Self {
+        Hittables::new()
+    }
+}
 impl Hittables<Uuid> {
     pub fn insert(&mut self, object: Object) {
         self.objects.insert(Uuid::new_v4(), object);
     }
 }
 
-impl<I: Sized + Sync> Hittable for Hittables<I> {
-    fn hit(&self, ray: &Ray, interval: Interval<f32>) -> Option<HitRecord> {
+impl<I: Sized + Copy> Hittables<I> {
+    /// Closest hit among the objects, with the key of the object hit.
+    pub fn hit_object(&self, ray: &Ray, interval: Interval<f32>) -> Option<(I, HitRecord)> {
         let mut current_interval = Interval {
             min: interval.min,
             max: interval.max,
         };
-        let mut current_record: Option<HitRecord> = None;
-        for (_, object) in &self.objects {
-            match object.mesh.hit(ray, current_interval) {
+        let mut current_record: Option<(I, HitRecord)> = None;
+        for (id, object) in &self.objects {
+            match object.hit(ray, current_interval) {
                 Some(record) => {
                     current_interval.max = record.t;
-                    current_record = Some(record);
+                    current_record = Some((*id, record));
                 }
                 None => {}
             }
@@ -45,3 +51,9 @@ impl<I: Sized + Sync> Hittable for Hittables<I> {
         current_record
     }
 }
+
+impl<I: Sized + Sync + Copy> Hittable for Hittables<I> {
+    fn hit(&self, ray: &Ray, interval: Interval<f32>) -> Option<HitRecord> {
+        self.hit_object(ray, interval).map(|(_, record)| record)
+    }
+}
@@ -1,15 +1,174 @@
-use super::hittable::Hittable;
+use crate::{interval::Interval, optical::ray::Ray, renderers::statistics};
+
+use super::{
+    hittable::{HitRecord, Hittable},
+    opacity::OpacityMask,
+};
+
+/// Upper bound of the masked-out surfaces a ray may pass through in one
+/// object, guarding against rays grazing along a surface.
+const MAX_MASKED_HITS: u32 = 64;
 
 pub struct Object {
     pub mesh: Box<dyn Hittable + Send>,
+    /// Cut-out mask of the mesh. Rays pass through transparent parts as if
+    /// there was no surface.
+    pub opacity: Option<OpacityMask>,
 }
 
-impl Object {}
+impl Object {
+    pub fn with_opacity(self, opacity: OpacityMask) -> Self {
+        Object {
+            opacity: Some(opacity),
+            ..self
+        }
+    }
+}
 
 impl<T: Hittable + Send + 'static> From<T> for Object {
     fn from(value: T) -> Self {
         Object {
             mesh: Box::new(value),
+            opacity: None,
+        }
+    }
+}
+
+impl Object {
+    /// Closest hit of the mesh, skipping the parts cut out by the opacity
+    /// mask.
+    pub fn hit(&self, ray: &Ray, interval: Interval<f32>) -> Option<HitRecord> {
+        let primitive = self.mesh.primitive_name();
+        statistics::record(|statistics| statistics.count_intersection(primitive));
+        let Some(opacity) = &self.opacity else {
+            return self.mesh.hit(ray, interval);
+        };
+
+        // Skip transparent hits and keep looking further along the ray.
+        let mut current_interval = interval;
+        for _ in 0..MAX_MASKED_HITS {
+            let record = self.mesh.hit(ray, current_interval)?;
+            if opacity.is_opaque(record.uv) {
+                return Some(record);
+            }
+            statistics::record(|statistics| statistics.count_intersection(primitive));
+            current_interval.min = record.t;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    };
+
+    use image::{Rgba, Rgba32FImage};
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{
+        objects::opacity::AlphaMode,
+        textures::image_texture::ImageTexture,
+        vectors::vector3::{Point3, Vector3},
+    };
+
+    fn record(t: f32, u: f32) -> HitRecord {
+        HitRecord {
+            point: Point3::zero(),
+            norm: Vector3::from((0.0, 0.0, 1.0)),
+            front_face: true,
+            t,
+            material_id: Uuid::nil(),
+            uv: (u, 0.5),
+            tangent: Vector3::from((1.0, 0.0, 0.0)),
+            bitangent: Vector3::from((0.0, 1.0, 0.0)),
+            uv_lengths: (1.0, 1.0),
+        }
+    }
+
+    /// Two parallel sheets at t = 1 (left half of the mask) and t = 2
+    /// (right half of the mask).
+    struct Sheets;
+
+    impl Hittable for Sheets {
+        fn hit(&self, _: &Ray, interval: Interval<f32>) -> Option<HitRecord> {
+            [record(1.0, 0.25), record(2.0, 0.75)]
+                .into_iter()
+                .find(|record| interval.surrounds(record.t))
+        }
+    }
+
+    /// Surface hit right after the start of every interval, counting the
+    /// queries.
+    struct Endless {
+        calls: Arc<AtomicU32>,
+    }
+
+    impl Hittable for Endless {
+        fn hit(&self, _: &Ray, interval: Interval<f32>) -> Option<HitRecord> {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            Some(record(interval.min + 0.01, 0.5))
+        }
+    }
+
+    fn mask(alpha: [f32; 2], mode: AlphaMode) -> OpacityMask {
+        OpacityMask {
+            texture: ImageTexture {
+                image: Rgba32FImage::from_fn(2, 1, |x, _| Rgba([1.0, 1.0, 1.0, alpha[x as usize]])),
+            },
+            use_luminance: false,
+            mode,
         }
     }
+
+    fn ray() -> Ray {
+        Ray::from((Point3::zero(), Vector3::from((0.0, 0.0, 1.0))))
+    }
+
+    #[test]
+    fn should_skip_hits_below_the_threshold() {
+        let object = Object::from(Sheets).with_opacity(mask([0.2, 1.0], AlphaMode::Threshold(0.5)));
+        let record = object
+            .hit(&ray(), Interval::from((0.001, f32::INFINITY)))
+            .unwrap();
+        assert_eq!(record.t, 2.0);
+
+        let object = Object::from(Sheets).with_opacity(mask([0.6, 1.0], AlphaMode::Threshold(0.5)));
+        let record = object
+            .hit(&ray(), Interval::from((0.001, f32::INFINITY)))
+            .unwrap();
+        assert_eq!(record.t, 1.0);
+    }
+
+    #[test]
+    fn should_pass_stochastically_through_transparent_parts() {
+        let object = Object::from(Sheets).with_opacity(mask([0.25, 0.25], AlphaMode::Stochastic));
+        let samples = 20000;
+        let mut passed = 0;
+        for _ in 0..samples {
+            if let Some(record) = object.hit(&ray(), Interval::from((0.001, 1.5))) {
+                assert_eq!(record.t, 1.0);
+            } else {
+                passed += 1;
+            }
+        }
+        let fraction = passed as f32 / samples as f32;
+        assert!((fraction - 0.75).abs() < 0.02, "{}", fraction);
+    }
+
+    #[test]
+    fn should_stop_after_the_maximum_of_masked_hits() {
+        let calls = Arc::new(AtomicU32::new(0));
+        let object = Object::from(Endless {
+            calls: calls.clone(),
+        })
+        .with_opacity(mask([0.0, 0.0], AlphaMode::Threshold(0.5)));
+        assert!(object
+            .hit(&ray(), Interval::from((0.001, f32::INFINITY)))
+            .is_none());
+        assert_eq!(calls.load(Ordering::Relaxed), MAX_MASKED_HITS);
+    }
 }
@@ -0,0 +1,37 @@
+use crate::{samplers::sampler::sample_1d, textures::image_texture::ImageTexture};
+
+pub enum AlphaMode {
+    /// Surfaces with alpha below `threshold` are cut out, the rest is opaque.
+    Threshold(f32),
+    /// Surfaces are hit with probability alpha, giving partial transparency
+    /// on average.
+    Stochastic,
+}
+
+/// Transparency mask over the uv coordinates of a shape (leaves, fences, ...).
+pub struct OpacityMask {
+    pub texture: ImageTexture,
+    /// Take alpha from the luminance of the texture instead of its alpha
+    /// channel, for grayscale masks.
+    pub use_luminance: bool,
+    pub mode: AlphaMode,
+}
+
+impl OpacityMask {
+    pub fn alpha(&self, uv: (f32, f32)) -> f32 {
+        if self.use_luminance {
+            self.texture.sample_gray(uv.0, uv.1)
+        } else {
+            self.texture.sample_alpha(uv.0, uv.1)
+        }
+    }
+
+    /// Whether the surface at `uv` stops the ray.
+    pub fn is_opaque(&self, uv: (f32, f32)) -> bool {
+        let alpha = self.alpha(uv);
+        match self.mode {
+            AlphaMode::Threshold(threshold) => alpha >= threshold,
+            AlphaMode::Stochastic => sample_1d() < alpha,
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use uuid::Uuid;
 
 use crate::{
-    geometry::coordinate::CoordinateSystem,
+    geometry::{axis::Axes3D, coordinate::CoordinateSystem},
     interval::Interval,
     optical::ray::Ray,
     vectors::{
@@ -47,12 +47,20 @@ impl Hittable for Plane {
         } else {
             let front_face = ray_dot_face < 0.0;
             let norm = if front_face { normal } else { -normal };
+            let point_from_00 = point - self.get_plane00_loc();
             Some(HitRecord {
                 point,
                 norm,
                 front_face,
                 t,
                 material_id: self.material_id,
+                uv: (
+                    point_from_00.dot(&self.coordinate.axes.u) / self.width,
+                    point_from_00.dot(&self.coordinate.axes.w) / self.height,
+                ),
+                tangent: self.coordinate.axes.u,
+                bitangent: self.coordinate.axes.w,
+                uv_lengths: (self.width, self.height),
             })
         }
     }
@@ -80,12 +88,21 @@ impl Hittable for InfinitePlane {
         } else {
             -self.normal
         };
+        let axes = Axes3D::around(self.normal);
+        let point_from_origin = point - self.origin;
         Some(HitRecord {
             point,
             norm,
             front_face,
             t,
             material_id: self.material_id,
+            uv: (
+                point_from_origin.dot(&axes.u),
+                point_from_origin.dot(&axes.v),
+            ),
+            tangent: axes.u,
+            bitangent: axes.v,
+            uv_lengths: (1.0, 1.0),
         })
     }
 }
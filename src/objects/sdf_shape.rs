@@ -0,0 +1,170 @@
+use uuid::Uuid;
+
+use crate::{
+    geometry::axis::Axes3D,
+    interval::Interval,
+    optical::ray::Ray,
+    sdf::distance::SignedDistance,
+    vectors::{
+        ops::MatrixDot,
+        vector3::{Point3, Vector3},
+    },
+};
+
+use super::hittable::{HitRecord, Hittable};
+
+/// Shape defined by a signed distance function, intersected by sphere
+/// tracing.
+pub struct SdfShape {
+    pub sdf: Box<dyn SignedDistance + Send>,
+    pub material_id: Uuid,
+    pub max_steps: u32,
+    /// Distance to the surface regarded as a hit.
+    pub epsilon: f32,
+    /// Rays travelling further than this (in world units) are regarded as
+    /// missing the shape, unless `interval` is shorter.
+    pub max_distance: f32,
+}
+
+impl SdfShape {
+    pub fn new(sdf: Box<dyn SignedDistance + Send>, material_id: Uuid) -> Self {
+        SdfShape {
+            sdf,
+            material_id,
+            max_steps: 256,
+            epsilon: 1.0e-4,
+            max_distance: 1.0e4,
+        }
+    }
+
+    /// Outward surface normal by central differences of the distance field.
+    fn normal(&self, point: Point3) -> Vector3 {
+        let h = self.epsilon;
+        let dx = Vector3::from((h, 0.0, 0.0));
+        let dy = Vector3::from((0.0, h, 0.0));
+        let dz = Vector3::from((0.0, 0.0, h));
+        Vector3::from((
+            self.sdf.distance(point + dx) - self.sdf.distance(point - dx),
+            self.sdf.distance(point + dy) - self.sdf.distance(point - dy),
+            self.sdf.distance(point + dz) - self.sdf.distance(point - dz),
+        ))
+        .to_unit()
+    }
+}
+
+impl Hittable for SdfShape {
+    fn hit(&self, ray: &Ray, interval: Interval<f32>) -> Option<HitRecord> {
+        let ray_length = ray.direction.norm();
+        let t_max = interval.max.min(self.max_distance / ray_length);
+
+        let mut t = interval.min.max(0.0);
+        // Rays starting inside of the shape trace the negated field
+        // to find where they leave it.
+        let sign = if self.sdf.distance(ray.at(t)) < 0.0 {
+            -1.0
+        } else {
+            1.0
+        };
+        // Step off the surface the ray starts from.
+        t += 2.0 * self.epsilon / ray_length;
+
+        for _ in 0..self.max_steps {
+            if t >= t_max {
+                return None;
+            }
+            let point = ray.at(t);
+            let distance = sign * self.sdf.distance(point);
+            if distance < self.epsilon {
+                if !interval.surrounds(t) {
+                    return None;
+                }
+                let outward = self.normal(point);
+                let front_face = ray.direction.dot(&outward) < 0.0;
+                let norm = if front_face { outward } else { -outward };
+                let frame = Axes3D::around(outward);
+                return Some(HitRecord {
+                    point,
+                    norm,
+                    front_face,
+                    t,
+                    material_id: self.material_id,
+                    uv: (0.0, 0.0),
+                    tangent: frame.u,
+                    bitangent: frame.v,
+                    uv_lengths: (1.0, 1.0),
+                });
+            }
+            t += distance / ray_length;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{objects::sphere::Sphere, sdf::primitives::SdfSphere};
+
+    #[test]
+    fn should_match_analytic_sphere() {
+        let center = Point3::from((0.0, 1.0, -3.0));
+        let sdf_sphere = SdfShape::new(Box::new(SdfSphere { center, r: 1.0 }), Uuid::nil());
+        let sphere = Sphere {
+            r: 1.0,
+            center,
+            material_id: Uuid::nil(),
+        };
+        let interval = Interval::from((0.001, f32::INFINITY));
+
+        // from outside
+        let ray = Ray::from((Point3::zero(), Vector3::from((0.1, 1.0, -3.0))));
+        let expected = sphere.hit(&ray, interval).unwrap();
+        let actual = sdf_sphere.hit(&ray, interval).unwrap();
+        assert!((expected.t - actual.t).abs() < 1.0e-3);
+        assert!((expected.norm - actual.norm).norm() < 1.0e-2);
+        assert!(actual.front_face);
+
+        // from inside
+        let ray = Ray::from((center, Vector3::from((0.0, 0.0, 2.0))));
+        let actual = sdf_sphere.hit(&ray, interval).unwrap();
+        assert!((actual.t - 0.5).abs() < 1.0e-3);
+        assert!(!actual.front_face);
+
+        // miss
+        let ray = Ray::from((Point3::zero(), Vector3::from((0.0, 0.0, 1.0))));
+        assert!(sdf_sphere.hit(&ray, interval).is_none());
+    }
+
+    #[test]
+    fn should_miss_grazing_rays() {
+        let sdf_sphere = SdfShape::new(
+            Box::new(SdfSphere {
+                center: Point3::zero(),
+                r: 1.0,
+            }),
+            Uuid::nil(),
+        );
+        let interval = Interval::from((0.001, f32::INFINITY));
+
+        // passing just outside of the sphere
+        let ray = Ray::from((
+            Point3::from((-5.0, 1.01, 0.0)),
+            Vector3::from((1.0, 0.0, 0.0)),
+        ));
+        assert!(sdf_sphere.hit(&ray, interval).is_none());
+
+        // just inside
+        let ray = Ray::from((
+            Point3::from((-5.0, 0.99, 0.0)),
+            Vector3::from((1.0, 0.0, 0.0)),
+        ));
+        let hit = sdf_sphere.hit(&ray, interval).unwrap();
+        let expected = 5.0 - (1.0 - 0.99f32.powi(2)).sqrt();
+        assert!(
+            (hit.t - expected).abs() < 1.0e-3,
+            "{} != {}",
+            hit.t,
+            expected
+        );
+    }
+}
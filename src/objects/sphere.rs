@@ -1,9 +1,13 @@
 use uuid::Uuid;
 
+use crate::geometry::axis::Axes3D;
 use crate::objects::hittable::{HitRecord, Hittable};
 use crate::optical::ray::Ray;
-use crate::vectors::ops::MatrixDot;
+use crate::vectors::ops::{MatrixCross, MatrixDot};
+use crate::vectors::utils::near_zero;
+use crate::vectors::vector3::Vector3;
 use crate::{interval::Interval, vectors::vector3::Point3};
+use std::f32::consts::PI;
 
 pub struct Sphere {
     pub r: f32,
@@ -11,6 +15,34 @@ pub struct Sphere {
     pub material_id: Uuid,
 }
 
+impl Sphere {
+    /// Spherical uv coordinates and tangents at the given outward normal.
+    /// u is the longitude from -x around the y axis, v the latitude from -y.
+    fn tangent_frame(&self, outward: &Vector3) -> ((f32, f32), Vector3, Vector3) {
+        let theta = (-outward.y).clamp(-1.0, 1.0).acos();
+        let phi = (-outward.z).atan2(outward.x) + PI;
+        let uv = (phi / (2.0 * PI), theta / PI);
+
+        let tangent = Vector3::from((outward.z, 0.0, -outward.x));
+        if near_zero(&tangent) {
+            // at the poles
+            let axes = Axes3D::around(*outward);
+            return (uv, axes.u, axes.v);
+        }
+        let tangent = tangent.to_unit();
+        (uv, tangent, outward.cross(&tangent))
+    }
+}
+
+impl Sphere {
+    /// |dp/du| along the circle of latitude and |dp/dv| along the meridian.
+    fn uv_lengths(&self, uv: (f32, f32)) -> (f32, f32) {
+        let radius = self.r.abs();
+        let latitude_radius = radius * (uv.1 * PI).sin();
+        ((2.0 * PI * latitude_radius).max(1.0e-6), PI * radius)
+    }
+}
+
 impl Hittable for Sphere {
     fn hit(&self, ray: &Ray, interval: Interval<f32>) -> Option<HitRecord> {
         let direction = &ray.direction;
@@ -38,6 +70,7 @@ impl Hittable for Sphere {
 
         let point = ray.at(root);
         let mut norm = (point - self.center) / self.r;
+        let (uv, tangent, bitangent) = self.tangent_frame(&norm);
         let front_face = direction.dot(&norm) < 0.0;
         if front_face == false {
             // ensure that norm is always against ray
@@ -49,6 +82,10 @@ impl Hittable for Sphere {
             t: root,
             front_face,
             material_id: self.material_id,
+            uv,
+            tangent,
+            bitangent,
+            uv_lengths: self.uv_lengths(uv),
         })
     }
 }
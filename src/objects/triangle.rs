@@ -1,23 +1,170 @@
-use crate::vectors::vector3::{Point3, Vector3};
+use uuid::Uuid;
 
-use super::hittable::Hittable;
+use crate::{
+    geometry::axis::Axes3D,
+    interval::Interval,
+    optical::ray::Ray,
+    vectors::{
+        ops::{MatrixCross, MatrixDot},
+        utils::near_zero,
+        vector3::{Point3, Vector3},
+    },
+};
+
+use super::hittable::{HitRecord, Hittable};
 
 pub struct Triangle {
     pub points: [Point3; 3],
+    /// Unit face normal, consistent with the counter-clockwise winding of
+    /// `points`.
     pub normal: Vector3,
+    /// Texture coordinates of each of `points`.
+    pub uvs: [(f32, f32); 3],
+    pub material_id: Uuid,
 }
 
 impl Triangle {
+    pub fn new(points: [Point3; 3], uvs: [(f32, f32); 3], material_id: Uuid) -> Self {
+        let normal = (points[1] - points[0])
+            .cross(&(points[2] - points[0]))
+            .to_unit();
+        Triangle {
+            points,
+            normal,
+            uvs,
+            material_id,
+        }
+    }
+
     pub fn gravity_center(&self) -> Point3 {
         (self.points[0] + self.points[1] + self.points[2]) / 3.0
     }
+
+    /// Tangents along u and v derived from the uv derivatives over the
+    /// triangle edges, and the lengths of dp/du and dp/dv.
+    fn tangents(&self) -> (Vector3, Vector3, (f32, f32)) {
+        let edge1 = self.points[1] - self.points[0];
+        let edge2 = self.points[2] - self.points[0];
+        let (du1, dv1) = (self.uvs[1].0 - self.uvs[0].0, self.uvs[1].1 - self.uvs[0].1);
+        let (du2, dv2) = (self.uvs[2].0 - self.uvs[0].0, self.uvs[2].1 - self.uvs[0].1);
+        let determinant = du1 * dv2 - du2 * dv1;
+        if determinant.abs() < 1.0e-12 {
+            // degenerate uv mapping
+            let axes = Axes3D::around(self.normal);
+            return (axes.u, axes.v, (1.0, 1.0));
+        }
+        let tangent = (edge1 * dv2 - edge2 * dv1) / determinant;
+        let bitangent = (edge2 * du1 - edge1 * du2) / determinant;
+        let lengths = (tangent.norm(), bitangent.norm());
+
+        // Gram-Schmidt against the face normal
+        let tangent = tangent - self.normal * tangent.dot(&self.normal);
+        if near_zero(&tangent) {
+            let axes = Axes3D::around(self.normal);
+            return (axes.u, axes.v, (1.0, 1.0));
+        }
+        let tangent = tangent.to_unit();
+        let bitangent_sign = if self.normal.cross(&tangent).dot(&bitangent) < 0.0 {
+            -1.0
+        } else {
+            1.0
+        };
+        (
+            tangent,
+            self.normal.cross(&tangent) * bitangent_sign,
+            lengths,
+        )
+    }
 }
 
-// impl Hittable for Triangle {
-//     fn hit(
-//         &self,
-//         ray: &crate::optical::ray::Ray,
-//         interval: crate::interval::Interval<f32>,
-//     ) -> Option<super::hittable::HitRecord> {
-//     }
-// }
+impl Hittable for Triangle {
+    /// Möller-Trumbore ray-triangle intersection.
+    fn hit(&self, ray: &Ray, interval: Interval<f32>) -> Option<HitRecord> {
+        let edge1 = self.points[1] - self.points[0];
+        let edge2 = self.points[2] - self.points[0];
+        let p = ray.direction.cross(&edge2);
+        let determinant = edge1.dot(&p);
+        if determinant.abs() < 1.0e-8 {
+            // parallel to the triangle
+            return None;
+        }
+
+        let s = ray.origin - self.points[0];
+        let b1 = s.dot(&p) / determinant;
+        if !(0.0..=1.0).contains(&b1) {
+            return None;
+        }
+        let q = s.cross(&edge1);
+        let b2 = ray.direction.dot(&q) / determinant;
+        if b2 < 0.0 || b1 + b2 > 1.0 {
+            return None;
+        }
+        let t = edge2.dot(&q) / determinant;
+        if !interval.surrounds(t) {
+            return None;
+        }
+
+        let b0 = 1.0 - b1 - b2;
+        let uv = (
+            b0 * self.uvs[0].0 + b1 * self.uvs[1].0 + b2 * self.uvs[2].0,
+            b0 * self.uvs[0].1 + b1 * self.uvs[1].1 + b2 * self.uvs[2].1,
+        );
+        let front_face = ray.direction.dot(&self.normal) < 0.0;
+        let norm = if front_face {
+            self.normal
+        } else {
+            -self.normal
+        };
+        let (tangent, bitangent, uv_lengths) = self.tangents();
+        Some(HitRecord {
+            point: ray.at(t),
+            norm,
+            front_face,
+            t,
+            material_id: self.material_id,
+            uv,
+            tangent,
+            bitangent,
+            uv_lengths,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_hit_with_uv_and_tangents() {
+        // uv mapped onto x and y
+        let triangle = Triangle::new(
+            [
+                Point3::from((0.0, 0.0, 0.0)),
+                Point3::from((2.0, 0.0, 0.0)),
+                Point3::from((0.0, 2.0, 0.0)),
+            ],
+            [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)],
+            Uuid::nil(),
+        );
+        let ray = Ray::from((
+            Point3::from((0.5, 1.0, 1.0)),
+            Vector3::from((0.0, 0.0, -1.0)),
+        ));
+        let record = triangle
+            .hit(&ray, Interval::from((0.001, f32::INFINITY)))
+            .unwrap();
+        assert!((record.t - 1.0).abs() < 1.0e-6);
+        assert!((record.uv.0 - 0.25).abs() < 1.0e-6 && (record.uv.1 - 0.5).abs() < 1.0e-6);
+        assert!(record.front_face);
+        assert!((record.tangent - Vector3::<f32>::UNIT_X).norm() < 1.0e-6);
+        assert!((record.bitangent - Vector3::<f32>::UNIT_Y).norm() < 1.0e-6);
+
+        let miss = Ray::from((
+            Point3::from((1.5, 1.5, 1.0)),
+            Vector3::from((0.0, 0.0, -1.0)),
+        ));
+        assert!(triangle
+            .hit(&miss, Interval::from((0.001, f32::INFINITY)))
+            .is_none());
+    }
+}
@@ -1,2 +1,6 @@
+pub mod aperture;
+pub mod dispersion;
+pub mod microfacet;
 pub mod ray;
 pub mod scatter;
+pub mod thin_film;
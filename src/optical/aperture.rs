@@ -0,0 +1,145 @@
+use std::{f32::consts::PI, path::Path};
+
+use image::{DynamicImage, ImageResult};
+
+use crate::samplers::warp::unit_disk;
+
+/// Shape of the lens aperture, which gives out-of-focus highlights (bokeh)
+/// their shape. Points are in the unit disk of the defocus radius, x to the
+/// right and y up.
+pub enum Aperture {
+    /// Perfectly round aperture.
+    Disk,
+    /// Regular polygon formed by `count` straight blades.
+    Blades { count: u32, rotation_deg: f32 },
+    /// Arbitrary shape given by the brightness of an image.
+    Image(ApertureImage),
+}
+
+impl Aperture {
+    /// Point of the aperture for the sample `u`, uniformly distributed over
+    /// its open area.
+    pub fn sample(&self, u: (f32, f32)) -> (f32, f32) {
+        match self {
+            Aperture::Disk => {
+                let p = unit_disk(u);
+                (p.x, p.y)
+            }
+            Aperture::Blades {
+                count,
+                rotation_deg,
+            } => {
+                // Pick one of the triangles between the center and an edge,
+                // then a uniform point in it.
+                let count = (*count).max(3) as f32;
+                let edge = (u.0 * count).floor().min(count - 1.0);
+                let s = (u.0 * count - edge).sqrt();
+                let angle = |k: f32| rotation_deg.to_radians() + 2.0 * PI * k / count;
+                let (a, b) = (angle(edge), angle(edge + 1.0));
+                let (wa, wb) = (s * (1.0 - u.1), s * u.1);
+                (wa * a.cos() + wb * b.cos(), wa * a.sin() + wb * b.sin())
+            }
+            Aperture::Image(image) => image.sample(u),
+        }
+    }
+}
+
+/// Aperture shape from a grayscale image covering the square around the
+/// unit disk. Brighter pixels let through more light.
+pub struct ApertureImage {
+    width: usize,
+    height: usize,
+    /// Cumulative distribution of the rows.
+    marginal: Vec<f32>,
+    /// Cumulative distribution of the pixels in each row.
+    conditional: Vec<f32>,
+}
+
+/// Normalize the weights in place into a cumulative distribution. Uniform
+/// if all weights are zero.
+fn to_cdf(weights: &mut [f32]) -> f32 {
+    let total: f32 = weights.iter().sum();
+    let count = weights.len() as f32;
+    let mut sum = 0.0;
+    for (i, weight) in weights.iter_mut().enumerate() {
+        sum += *weight;
+        *weight = if total > 0.0 {
+            sum / total
+        } else {
+            (i + 1) as f32 / count
+        };
+    }
+    total
+}
+
+/// Entry of the cumulative distribution hit by `u`, with the position of
+/// `u` inside of it.
+fn sample_cdf(cdf: &[f32], u: f32) -> (usize, f32) {
+    let i = cdf.partition_point(|&c| c <= u).min(cdf.len() - 1);
+    let low = if i == 0 { 0.0 } else { cdf[i - 1] };
+    let t = (u - low) / (cdf[i] - low).max(f32::EPSILON);
+    (i, t.clamp(0.0, 1.0))
+}
+
+impl ApertureImage {
+    pub fn new(image: &DynamicImage) -> Self {
+        let luma = image.to_luma32f();
+        let (width, height) = (luma.width() as usize, luma.height() as usize);
+        let mut conditional: Vec<f32> = luma.pixels().map(|p| p.0[0].max(0.0)).collect();
+        let mut marginal: Vec<f32> = conditional.chunks_mut(width).map(to_cdf).collect();
+        to_cdf(&mut marginal);
+        ApertureImage {
+            width,
+            height,
+            marginal,
+            conditional,
+        }
+    }
+
+    pub fn load(path: &Path) -> ImageResult<Self> {
+        Ok(Self::new(&image::open(path)?))
+    }
+
+    fn sample(&self, u: (f32, f32)) -> (f32, f32) {
+        let (y, ty) = sample_cdf(&self.marginal, u.1);
+        let row = &self.conditional[y * self.width..(y + 1) * self.width];
+        let (x, tx) = sample_cdf(row, u.0);
+        (
+            2.0 * (x as f32 + tx) / self.width as f32 - 1.0,
+            1.0 - 2.0 * (y as f32 + ty) / self.height as f32,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{GrayImage, Luma};
+
+    use super::*;
+
+    #[test]
+    fn should_sample_inside_the_aperture() {
+        let blades = Aperture::Blades {
+            count: 5,
+            rotation_deg: 18.0,
+        };
+        let apothem = (PI / 5.0).cos();
+        let image = Aperture::Image(ApertureImage::new(&DynamicImage::ImageLuma8(
+            GrayImage::from_fn(4, 4, |x, y| Luma([if (x, y) == (3, 0) { 255 } else { 0 }])),
+        )));
+        for i in 0..16 {
+            for j in 0..16 {
+                let u = ((i as f32 + 0.5) / 16.0, (j as f32 + 0.5) / 16.0);
+
+                let (x, y) = blades.sample(u);
+                for k in 0..5 {
+                    let normal = (18.0f32 + 36.0 + 72.0 * k as f32).to_radians();
+                    assert!(x * normal.cos() + y * normal.sin() <= apothem + 1e-5);
+                }
+
+                let (x, y) = image.sample(u);
+                assert!((0.5..=1.0).contains(&x) && (0.5..=1.0).contains(&y));
+            }
+        }
+    }
+}
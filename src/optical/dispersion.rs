@@ -0,0 +1,98 @@
+/// Wavelength dependent index of refraction. Wavelengths are in nm.
+pub enum Dispersion {
+    Constant(f32),
+    /// Cauchy's equation n = a + b / lambda^2, with lambda in micrometers.
+    Cauchy {
+        a: f32,
+        b: f32,
+    },
+    /// Sellmeier equation n^2 = 1 + sum(b_i lambda^2 / (lambda^2 - c_i)),
+    /// with lambda in micrometers and `c` in micrometers squared.
+    Sellmeier {
+        b: [f32; 3],
+        c: [f32; 3],
+    },
+}
+
+/// Wavelength of the Fraunhofer d line, where indices of refraction are
+/// commonly specified.
+pub const LAMBDA_D: f32 = 587.6;
+
+impl Dispersion {
+    /// Borosilicate crown glass.
+    pub fn bk7() -> Self {
+        Dispersion::Sellmeier {
+            b: [1.039_612, 0.231_792_34, 1.010_469_4],
+            c: [0.006_000_698_7, 0.020_017_914, 103.560_65],
+        }
+    }
+
+    pub fn fused_silica() -> Self {
+        Dispersion::Sellmeier {
+            b: [0.696_166_3, 0.407_942_6, 0.897_479_4],
+            c: [0.004_679_148, 0.013_512_063, 97.934],
+        }
+    }
+
+    /// Strongly dispersive flint glass.
+    pub fn dense_flint() -> Self {
+        Dispersion::Cauchy {
+            a: 1.728,
+            b: 0.013_42,
+        }
+    }
+
+    pub fn index_of_refraction(&self, lambda: f32) -> f32 {
+        let lambda_um2 = (lambda / 1000.0).powi(2);
+        match self {
+            Dispersion::Constant(n) => *n,
+            Dispersion::Cauchy { a, b } => a + b / lambda_um2,
+            Dispersion::Sellmeier { b, c } => {
+                let mut n2 = 1.0;
+                for i in 0..3 {
+                    n2 += b[i] * lambda_um2 / (lambda_um2 - c[i]);
+                }
+                n2.sqrt()
+            }
+        }
+    }
+
+    pub fn is_constant(&self) -> bool {
+        matches!(self, Dispersion::Constant(_))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_match_the_catalog_indices_of_bk7() {
+        let bk7 = Dispersion::bk7();
+        // Fraunhofer F, d and C lines
+        for (lambda, expected) in [(486.1, 1.52238), (LAMBDA_D, 1.51680), (656.3, 1.51432)] {
+            let n = bk7.index_of_refraction(lambda);
+            assert!(
+                (n - expected).abs() < 2e-4,
+                "{}: {} != {}",
+                lambda,
+                n,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn should_disperse_shorter_wavelengths_more() {
+        for dispersion in [
+            Dispersion::bk7(),
+            Dispersion::fused_silica(),
+            Dispersion::dense_flint(),
+        ] {
+            assert!(dispersion.index_of_refraction(450.0) > dispersion.index_of_refraction(650.0));
+        }
+        let constant = Dispersion::Constant(1.5);
+        assert_eq!(constant.index_of_refraction(450.0), 1.5);
+        assert_eq!(constant.index_of_refraction(650.0), 1.5);
+    }
+}
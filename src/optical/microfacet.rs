@@ -0,0 +1,89 @@
+use std::f32::consts::PI;
+
+use crate::vectors::vector3::Vector3;
+
+/// GGX (Trowbridge-Reitz) microfacet distribution with Smith masking.
+///
+/// All directions are in the local shading frame where the macro surface
+/// normal is +z, and point away from the surface.
+/// https://jcgt.org/published/0007/04/01/
+pub struct Ggx {
+    pub alpha: f32,
+}
+
+impl Ggx {
+    /// Map perceptual roughness in [0, 1] to the distribution width.
+    pub fn from_roughness(roughness: f32) -> Self {
+        Ggx {
+            alpha: roughness.powi(2).max(1.0e-4),
+        }
+    }
+
+    /// Distribution of micro normals `m`.
+    pub fn d(&self, m: &Vector3) -> f32 {
+        if m.z <= 0.0 {
+            return 0.0;
+        }
+        let a2 = self.alpha.powi(2);
+        let denominator = (a2 - 1.0) * m.z.powi(2) + 1.0;
+        a2 / (PI * denominator.powi(2))
+    }
+
+    /// Smith's auxiliary function.
+    pub fn lambda(&self, w: &Vector3) -> f32 {
+        let cos2 = w.z.powi(2);
+        if cos2 <= 0.0 {
+            return f32::INFINITY;
+        }
+        let tan2 = (1.0 - cos2).max(0.0) / cos2;
+        (-1.0 + (1.0 + self.alpha.powi(2) * tan2).sqrt()) / 2.0
+    }
+
+    /// Masking function.
+    pub fn g1(&self, w: &Vector3) -> f32 {
+        1.0 / (1.0 + self.lambda(w))
+    }
+
+    /// Height-correlated masking-shadowing function.
+    pub fn g2(&self, wo: &Vector3, wi: &Vector3) -> f32 {
+        1.0 / (1.0 + self.lambda(wo) + self.lambda(wi))
+    }
+
+    /// Density of visible normals seen from `wo`.
+    pub fn visible_d(&self, wo: &Vector3, m: &Vector3) -> f32 {
+        if wo.z <= 0.0 {
+            return 0.0;
+        }
+        let cos_om = (wo.x * m.x + wo.y * m.y + wo.z * m.z).max(0.0);
+        self.g1(wo) * cos_om * self.d(m) / wo.z
+    }
+
+    /// Sample a micro normal proportional to `visible_d` (Heitz 2018).
+    pub fn sample_visible_normal(&self, wo: &Vector3, u1: f32, u2: f32) -> Vector3 {
+        let alpha = self.alpha;
+        // stretch the view direction to the hemisphere configuration
+        let vh = Vector3::from((alpha * wo.x, alpha * wo.y, wo.z)).to_unit();
+        let len2 = vh.x.powi(2) + vh.y.powi(2);
+        let t1 = if len2 > 0.0 {
+            Vector3::from((-vh.y, vh.x, 0.0)) / len2.sqrt()
+        } else {
+            Vector3::<f32>::UNIT_X
+        };
+        let t2 = Vector3::from((
+            vh.y * t1.z - vh.z * t1.y,
+            vh.z * t1.x - vh.x * t1.z,
+            vh.x * t1.y - vh.y * t1.x,
+        ));
+
+        // sample the projected area
+        let r = u1.sqrt();
+        let phi = 2.0 * PI * u2;
+        let p1 = r * phi.cos();
+        let s = 0.5 * (1.0 + vh.z);
+        let p2 = (1.0 - s) * (1.0 - p1.powi(2)).sqrt() + s * r * phi.sin();
+        let nh = t1 * p1 + t2 * p2 + vh * (1.0 - p1.powi(2) - p2.powi(2)).max(0.0).sqrt();
+
+        // unstretch
+        Vector3::from((alpha * nh.x, alpha * nh.y, nh.z.max(1.0e-6))).to_unit()
+    }
+}
@@ -1,4 +1,8 @@
-use crate::vectors::{ops::MatrixDot, vector3::Vector3};
+use crate::{
+    color::Color,
+    samplers::sampler::sample_1d,
+    vectors::{ops::MatrixDot, vector3::Vector3},
+};
 
 /// Check if refract is able to occur with granted direction of light, normal of surface, and refraction ratio
 pub fn refractable(cos_theta_in: f32, refraction_ratio: f32) -> bool {
@@ -34,6 +38,57 @@ pub fn reflectance(cosine_theta_in: f32, reflection_ratio: f32) -> f32 {
     r0 + (1.0 - r0) * (1.0 - cosine_theta_in).powi(5)
 }
 
+/// Exact unpolarized Fresnel reflectance of a conductor with complex index of
+/// refraction `eta + i k` relative to the outside medium.
+pub fn fresnel_conductor(cosine_theta_in: f32, eta: Color, k: Color) -> Color {
+    let channel = |eta: f32, k: f32| -> f32 {
+        let cos2 = cosine_theta_in.clamp(0.0, 1.0).powi(2);
+        let sin2 = 1.0 - cos2;
+        let eta2 = eta.powi(2);
+        let k2 = k.powi(2);
+
+        let t0 = eta2 - k2 - sin2;
+        let a2_plus_b2 = (t0.powi(2) + 4.0 * eta2 * k2).sqrt();
+        let t1 = a2_plus_b2 + cos2;
+        let a = (0.5 * (a2_plus_b2 + t0)).max(0.0).sqrt();
+        let t2 = 2.0 * cosine_theta_in * a;
+        let rs = (t1 - t2) / (t1 + t2);
+
+        let t3 = cos2 * a2_plus_b2 + sin2.powi(2);
+        let t4 = t2 * sin2;
+        let rp = rs * (t3 - t4) / (t3 + t4);
+        0.5 * (rp + rs)
+    };
+    Color::from((
+        channel(eta.x, k.x),
+        channel(eta.y, k.y),
+        channel(eta.z, k.z),
+    ))
+}
+
+/// Exact unpolarized Fresnel reflectance at a dielectric interface.
+pub fn fresnel_dielectric(cosine_theta_in: f32, eta_i_over_t: f32) -> f32 {
+    let cos_i = cosine_theta_in.clamp(0.0, 1.0);
+    let sin2_t = eta_i_over_t.powi(2) * (1.0 - cos_i.powi(2));
+    if sin2_t >= 1.0 {
+        // total internal reflection
+        return 1.0;
+    }
+    let cos_t = (1.0 - sin2_t).sqrt();
+    let rs = (eta_i_over_t * cos_i - cos_t) / (eta_i_over_t * cos_i + cos_t);
+    let rp = (cos_i - eta_i_over_t * cos_t) / (cos_i + eta_i_over_t * cos_t);
+    0.5 * (rs.powi(2) + rp.powi(2))
+}
+
+/// Transmittance through an absorbing medium over the given distance.
+pub fn beer_lambert(absorption: Color, distance: f32) -> Color {
+    Color::from((
+        (-absorption.x * distance).exp(),
+        (-absorption.y * distance).exp(),
+        (-absorption.z * distance).exp(),
+    ))
+}
+
 pub fn refract_or_reflect(
     vec: &Vector3<f32>,
     norm: &Vector3<f32>,
@@ -41,9 +96,27 @@ pub fn refract_or_reflect(
 ) -> Vector3<f32> {
     let cos_theta = -vec.dot(norm) / (vec.norm() * norm.norm());
     let reflectance = reflectance(cos_theta, refraction_ratio);
-    if !refractable(cos_theta, refraction_ratio) || reflectance > rand::random() {
+    if !refractable(cos_theta, refraction_ratio) || reflectance > sample_1d() {
         reflect(vec, norm)
     } else {
         refract(vec, norm, refraction_ratio).unwrap()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_attenuate_exponentially_with_distance() {
+        let absorption = Color::from((0.1, 0.5, 2.0));
+        let transmittance = beer_lambert(absorption, 1.5);
+        for channel in 0..3 {
+            let expected = (-absorption[channel] * 1.5).exp();
+            assert!((transmittance[channel] - expected).abs() < 1e-6);
+        }
+        assert_eq!(beer_lambert(absorption, 0.0).x, 1.0);
+        let clear = beer_lambert(Color::zero(), 100.0);
+        assert_eq!((clear.x, clear.y, clear.z), (1.0, 1.0, 1.0));
+    }
+}
@@ -0,0 +1,231 @@
+use std::{f32::consts::PI, ops};
+
+use crate::{color::Color, spectrum::cie, textures::image_texture::ImageTexture};
+
+/// Minimal complex arithmetic for the Fresnel amplitudes.
+struct Complex {
+    re: f32,
+    im: f32,
+}
+
+impl Complex {
+    fn new(re: f32, im: f32) -> Self {
+        Complex { re, im }
+    }
+    fn norm_squared(&self) -> f32 {
+        self.re.powi(2) + self.im.powi(2)
+    }
+    fn sqrt(&self) -> Complex {
+        let r = self.norm_squared().sqrt().sqrt();
+        let theta = self.im.atan2(self.re) / 2.0;
+        Complex::new(r * theta.cos(), r * theta.sin())
+    }
+    /// exp(i * self)
+    fn exp_i(&self) -> Complex {
+        let magnitude = (-self.im).exp();
+        Complex::new(magnitude * self.re.cos(), magnitude * self.re.sin())
+    }
+}
+
+impl Copy for Complex {}
+impl Clone for Complex {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl ops::Add for Complex {
+    type Output = Complex;
+    fn add(self, rhs: Complex) -> Complex {
+        Complex::new(self.re + rhs.re, self.im + rhs.im)
+    }
+}
+impl ops::Sub for Complex {
+    type Output = Complex;
+    fn sub(self, rhs: Complex) -> Complex {
+        Complex::new(self.re - rhs.re, self.im - rhs.im)
+    }
+}
+impl ops::Mul for Complex {
+    type Output = Complex;
+    fn mul(self, rhs: Complex) -> Complex {
+        Complex::new(
+            self.re * rhs.re - self.im * rhs.im,
+            self.re * rhs.im + self.im * rhs.re,
+        )
+    }
+}
+impl ops::Div for Complex {
+    type Output = Complex;
+    fn div(self, rhs: Complex) -> Complex {
+        let denominator = rhs.norm_squared();
+        Complex::new(
+            (self.re * rhs.re + self.im * rhs.im) / denominator,
+            (self.im * rhs.re - self.re * rhs.im) / denominator,
+        )
+    }
+}
+
+/// Cosine of the refracted angle, complex beyond the critical angle or in
+/// absorbing media.
+fn cos_transmitted(n_i: Complex, n_t: Complex, sin2_i: f32) -> Complex {
+    let ratio = n_i / n_t;
+    let one = Complex::new(1.0, 0.0);
+    (one - ratio * ratio * Complex::new(sin2_i, 0.0)).sqrt()
+}
+
+/// Fresnel amplitude coefficients (s, p) of an interface.
+fn amplitudes(n_i: Complex, cos_i: Complex, n_t: Complex, cos_t: Complex) -> (Complex, Complex) {
+    let rs = (n_i * cos_i - n_t * cos_t) / (n_i * cos_i + n_t * cos_t);
+    let rp = (n_t * cos_i - n_i * cos_t) / (n_t * cos_i + n_i * cos_t);
+    (rs, rp)
+}
+
+/// Value of an RGB quantity at the wavelength, interpolating between
+/// representative wavelengths of the channels.
+pub fn rgb_at_wavelength(color: &Color, lambda: f32) -> f32 {
+    const RED: f32 = 650.0;
+    const GREEN: f32 = 550.0;
+    const BLUE: f32 = 450.0;
+    if lambda <= BLUE {
+        color.z
+    } else if lambda <= GREEN {
+        let t = (lambda - BLUE) / (GREEN - BLUE);
+        color.z * (1.0 - t) + color.y * t
+    } else if lambda <= RED {
+        let t = (lambda - GREEN) / (RED - GREEN);
+        color.y * (1.0 - t) + color.x * t
+    } else {
+        color.x
+    }
+}
+
+pub enum FilmThickness {
+    /// Thickness in nm.
+    Constant(f32),
+    /// Thickness in nm mapped from the gray level of the texture at the uv
+    /// coordinates of the hit.
+    Textured {
+        texture: ImageTexture,
+        min: f32,
+        max: f32,
+    },
+}
+
+/// Thin dielectric film coating a surface (soap bubbles, oil slicks,
+/// anodized metal), causing interference colors.
+pub struct ThinFilm {
+    pub thickness: FilmThickness,
+    pub index_of_refraction: f32,
+}
+
+impl ThinFilm {
+    pub fn thickness_at(&self, uv: (f32, f32)) -> f32 {
+        match &self.thickness {
+            FilmThickness::Constant(thickness) => *thickness,
+            FilmThickness::Textured { texture, min, max } => {
+                min + (max - min) * texture.sample_gray(uv.0, uv.1).clamp(0.0, 1.0)
+            }
+        }
+    }
+
+    /// Reflectance of the film for light arriving from a medium of index
+    /// `n_incident` onto a substrate of complex index `eta + i k`, summing
+    /// the multiple reflections inside of the film (Airy summation).
+    pub fn reflectance(
+        &self,
+        cosine_theta_in: f32,
+        lambda: f32,
+        thickness: f32,
+        n_incident: f32,
+        substrate: (f32, f32),
+    ) -> f32 {
+        let cos_1 = cosine_theta_in.clamp(0.0, 1.0);
+        let sin2_1 = 1.0 - cos_1.powi(2);
+        let n_1 = Complex::new(n_incident, 0.0);
+        let n_2 = Complex::new(self.index_of_refraction, 0.0);
+        let n_3 = Complex::new(substrate.0, substrate.1);
+
+        let cos_1 = Complex::new(cos_1, 0.0);
+        let cos_2 = cos_transmitted(n_1, n_2, sin2_1);
+        let cos_3 = cos_transmitted(n_1, n_3, sin2_1);
+
+        let (r12_s, r12_p) = amplitudes(n_1, cos_1, n_2, cos_2);
+        let (r23_s, r23_p) = amplitudes(n_2, cos_2, n_3, cos_3);
+
+        // phase difference of one round trip through the film
+        let phase = (n_2 * cos_2 * Complex::new(4.0 * PI * thickness / lambda, 0.0)).exp_i();
+        let one = Complex::new(1.0, 0.0);
+        let airy = |r12: Complex, r23: Complex| -> f32 {
+            ((r12 + r23 * phase) / (one + r12 * r23 * phase))
+                .norm_squared()
+                .clamp(0.0, 1.0)
+        };
+        0.5 * (airy(r12_s, r23_s) + airy(r12_p, r23_p))
+    }
+
+    /// Reflectance integrated against the color matching functions, for RGB
+    /// rendering. `substrate` gives the complex index at a wavelength.
+    pub fn reflectance_rgb(
+        &self,
+        cosine_theta_in: f32,
+        thickness: f32,
+        n_incident: f32,
+        substrate: &dyn Fn(f32) -> (f32, f32),
+    ) -> Color {
+        const SAMPLES: usize = 16;
+        const LAMBDA_MIN: f32 = 380.0;
+        const LAMBDA_MAX: f32 = 780.0;
+
+        let mut xyz = Color::zero();
+        let mut y_integral = 0.0;
+        for i in 0..SAMPLES {
+            let lambda = LAMBDA_MIN + (LAMBDA_MAX - LAMBDA_MIN) * (i as f32 + 0.5) / SAMPLES as f32;
+            let matching = cie::xyz_matching(lambda);
+            let reflectance = self.reflectance(
+                cosine_theta_in,
+                lambda,
+                thickness,
+                n_incident,
+                substrate(lambda),
+            );
+            xyz += matching * reflectance;
+            y_integral += matching.y;
+        }
+        let rgb = cie::xyz_to_rgb(&(xyz / y_integral));
+        Color::from((
+            rgb.x.clamp(0.0, 1.0),
+            rgb.y.clamp(0.0, 1.0),
+            rgb.z.clamp(0.0, 1.0),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_match_fresnel_without_film() {
+        // A film with the index of the incident medium has no effect.
+        let film = ThinFilm {
+            thickness: FilmThickness::Constant(300.0),
+            index_of_refraction: 1.0,
+        };
+        let reflectance = film.reflectance(1.0, 550.0, 300.0, 1.0, (1.5, 0.0));
+        assert!((reflectance - 0.04).abs() < 1.0e-4);
+    }
+
+    #[test]
+    fn should_cancel_reflection_of_quarter_wave_coating() {
+        // anti-reflection coating: n_film = sqrt(n_glass), d = lambda / 4n
+        let n_film = 1.5f32.sqrt();
+        let thickness = 550.0 / (4.0 * n_film);
+        let film = ThinFilm {
+            thickness: FilmThickness::Constant(thickness),
+            index_of_refraction: n_film,
+        };
+        let reflectance = film.reflectance(1.0, 550.0, thickness, 1.0, (1.5, 0.0));
+        assert!(reflectance < 1.0e-4, "{}", reflectance);
+    }
+}
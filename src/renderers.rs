@@ -1,2 +1,15 @@
+pub mod accumulator;
+pub mod adaptive;
+pub mod aov;
 pub mod camera;
+pub mod camera_model;
+pub mod checkpoint;
+pub mod denoiser;
+pub mod filter;
+pub mod framebuffer;
+pub mod panoramic;
+pub mod progress;
+pub mod progressive;
 pub mod renderer;
+pub mod statistics;
+pub mod stereo;
@@ -0,0 +1,193 @@
+use crate::{
+    color::Color,
+    vectors::{ops::MatrixDot, vector3::Vector3},
+};
+
+use super::{
+    adaptive::PixelStatistics,
+    aov::{id_color, Aov, PathSample, RenderPasses},
+    filter::Filter,
+    framebuffer::{FrameBuffer, SplatBuffer},
+};
+
+/// Running sums of the samples of every pixel, so a render can add samples
+/// in several passes and be resolved into images at any time.
+pub struct Accumulator {
+    /// Beauty splatted through the reconstruction filter.
+    pub film: SplatBuffer,
+    /// Samples taken per pixel.
+    pub sample_counts: Vec<u32>,
+    /// Samples per pixel that hit a surface.
+    pub hit_counts: Vec<u32>,
+    /// Luminance statistics per pixel, for adaptive sampling.
+    pub statistics: Vec<PixelStatistics>,
+    /// Sums of the passes. Id passes hold the id of the first hit instead.
+    pub aovs: Vec<(Aov, FrameBuffer)>,
+    /// Sums of the even and of the odd samples of each pixel.
+    pub halves: Option<(FrameBuffer, FrameBuffer)>,
+}
+
+impl Accumulator {
+    pub fn new(aovs: &[Aov], width: u32, height: u32, halves: bool) -> Self {
+        let pixels = (width * height) as usize;
+        Accumulator {
+            film: SplatBuffer::new(width, height),
+            sample_counts: vec![0; pixels],
+            hit_counts: vec![0; pixels],
+            statistics: (0..pixels).map(|_| PixelStatistics::new()).collect(),
+            aovs: aovs
+                .iter()
+                .map(|aov| (*aov, FrameBuffer::new(width, height)))
+                .collect(),
+            halves: if halves {
+                Some((
+                    FrameBuffer::new(width, height),
+                    FrameBuffer::new(width, height),
+                ))
+            } else {
+                None
+            },
+        }
+    }
+
+    /// Whether both accumulate the same image size and buffers.
+    pub fn matches(&self, other: &Accumulator) -> bool {
+        self.width() == other.width()
+            && self.height() == other.height()
+            && self.halves.is_some() == other.halves.is_some()
+            && self.aovs.len() == other.aovs.len()
+            && self.aovs.iter().zip(&other.aovs).all(|(a, b)| a.0 == b.0)
+    }
+
+    pub fn width(&self) -> u32 {
+        self.film.sum.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.film.sum.height
+    }
+
+    pub fn sample_count(&self, x: u32, y: u32) -> u32 {
+        self.sample_counts[self.film.sum.index(x, y)]
+    }
+
+    pub fn pixel_statistics(&self, x: u32, y: u32) -> &PixelStatistics {
+        &self.statistics[self.film.sum.index(x, y)]
+    }
+
+    /// Add a sample taken at `offset` from the center of pixel (x, y).
+    pub fn add(
+        &mut self,
+        filter: &Filter,
+        x: u32,
+        y: u32,
+        offset: (f32, f32),
+        sample: &PathSample,
+    ) {
+        let index = self.film.sum.index(x, y);
+        self.film.splat(filter, x, y, offset, sample.color);
+        self.statistics[index].add(&sample.color);
+
+        let first_hit = sample.first_hit.as_ref();
+        for (aov, buffer) in self.aovs.iter_mut() {
+            let pixel = &mut buffer.pixels[index];
+            match aov {
+                Aov::Depth => {
+                    if let Some(hit) = first_hit {
+                        *pixel += hit.depth;
+                    }
+                }
+                Aov::Normal => {
+                    if let Some(hit) = first_hit {
+                        *pixel += hit.normal;
+                    }
+                }
+                Aov::Albedo => {
+                    *pixel += first_hit.map_or(Color::from((1.0, 1.0, 1.0)), |hit| hit.albedo);
+                }
+                // Ids are not averaged, the first surface seen represents
+                // the pixel.
+                Aov::MaterialId => {
+                    if let (Some(hit), 0) = (first_hit, self.hit_counts[index]) {
+                        *pixel = id_color(&hit.material_id);
+                    }
+                }
+                Aov::ObjectId => {
+                    if let (Some(hit), 0) = (first_hit, self.hit_counts[index]) {
+                        *pixel = id_color(&hit.object_id);
+                    }
+                }
+                Aov::DirectDiffuse => *pixel += sample.direct_diffuse,
+                Aov::IndirectDiffuse => *pixel += sample.indirect_diffuse,
+                Aov::DirectSpecular => *pixel += sample.direct_specular,
+                Aov::IndirectSpecular => *pixel += sample.indirect_specular,
+                Aov::SampleCount => {}
+            }
+        }
+        if let Some((even, odd)) = &mut self.halves {
+            let half = if self.sample_counts[index].is_multiple_of(2) {
+                even
+            } else {
+                odd
+            };
+            half.pixels[index] += sample.color;
+        }
+        self.sample_counts[index] += 1;
+        if first_hit.is_some() {
+            self.hit_counts[index] += 1;
+        }
+    }
+
+    /// Images of the samples so far.
+    pub fn resolve(&self) -> RenderPasses {
+        let average = |sum: Vector3, count: u32| {
+            if count == 0 {
+                sum
+            } else {
+                sum / count as f32
+            }
+        };
+        let aovs = self
+            .aovs
+            .iter()
+            .map(|(aov, buffer)| {
+                let mut image = FrameBuffer::new(buffer.width, buffer.height);
+                for (i, pixel) in image.pixels.iter_mut().enumerate() {
+                    let (samples, hits) = (self.sample_counts[i], self.hit_counts[i]);
+                    let sum = buffer.pixels[i];
+                    *pixel = match aov {
+                        Aov::Depth => average(sum, hits),
+                        Aov::Normal => {
+                            if sum.dot(&sum) > 0.0 {
+                                sum.to_unit()
+                            } else {
+                                sum
+                            }
+                        }
+                        Aov::MaterialId | Aov::ObjectId => sum,
+                        Aov::SampleCount => {
+                            Color::from((samples as f32, samples as f32, samples as f32))
+                        }
+                        _ => average(sum, samples),
+                    };
+                }
+                (*aov, image)
+            })
+            .collect();
+        let halves = self.halves.as_ref().map(|(even, odd)| {
+            let mut even_image = FrameBuffer::new(even.width, even.height);
+            let mut odd_image = FrameBuffer::new(odd.width, odd.height);
+            for i in 0..even.pixels.len() {
+                let samples = self.sample_counts[i];
+                even_image.pixels[i] = average(even.pixels[i], samples.div_ceil(2));
+                odd_image.pixels[i] = average(odd.pixels[i], samples / 2);
+            }
+            (even_image, odd_image)
+        });
+        RenderPasses {
+            beauty: self.film.resolve(),
+            aovs,
+            halves,
+        }
+    }
+}
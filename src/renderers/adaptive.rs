@@ -0,0 +1,108 @@
+use crate::color::Color;
+
+/// Luminance of a linear sRGB color.
+pub fn luminance(color: &Color) -> f32 {
+    0.2126 * color.x + 0.7152 * color.y + 0.0722 * color.z
+}
+
+/// Running mean and variance of the luminance of the samples of a pixel
+/// (Welford's algorithm).
+pub struct PixelStatistics {
+    pub count: u32,
+    pub mean: f32,
+    /// Sum of squared differences from the mean.
+    pub m2: f32,
+}
+
+impl Default for PixelStatistics {
+    fn default() -> Self {
+        PixelStatistics::new()
+    }
+}
+
+impl PixelStatistics {
+    pub fn new() -> Self {
+        PixelStatistics {
+            count: 0,
+            mean: 0.0,
+            m2: 0.0,
+        }
+    }
+
+    pub fn add(&mut self, color: &Color) {
+        let value = luminance(color);
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f32;
+        self.m2 += delta * (value - self.mean);
+    }
+
+    /// Unbiased sample variance.
+    pub fn variance(&self) -> f32 {
+        if self.count < 2 {
+            f32::INFINITY
+        } else {
+            self.m2 / (self.count - 1) as f32
+        }
+    }
+
+    /// Estimated standard error of the displayed pixel value. With the
+    /// gamma of 2 used for output, d(sqrt(L)) = dL / (2 sqrt(L)).
+    pub fn error(&self) -> f32 {
+        let standard_error = (self.variance() / self.count as f32).sqrt();
+        standard_error / (2.0 * self.mean.max(1.0e-4).sqrt())
+    }
+}
+
+/// Stop sampling pixels once their estimated error is small enough.
+pub struct AdaptiveSampling {
+    pub min_samples: u32,
+    pub max_samples: u32,
+    /// Error of the displayed value (in [0, 1]) below which a pixel is
+    /// considered converged.
+    pub threshold: f32,
+    /// Samples added between two convergence checks.
+    pub batch_size: u32,
+}
+
+impl Default for AdaptiveSampling {
+    fn default() -> Self {
+        AdaptiveSampling {
+            min_samples: 16,
+            max_samples: 512,
+            threshold: 0.01,
+            batch_size: 16,
+        }
+    }
+}
+
+impl AdaptiveSampling {
+    pub fn converged(&self, statistics: &PixelStatistics) -> bool {
+        statistics.count >= self.max_samples
+            || (statistics.count >= self.min_samples && statistics.error() < self.threshold)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_converge_on_constant_pixels_only() {
+        let adaptive = AdaptiveSampling::default();
+
+        let mut flat = PixelStatistics::new();
+        for _ in 0..adaptive.min_samples {
+            flat.add(&Color::from((0.5, 0.7, 1.0)));
+        }
+        assert!(adaptive.converged(&flat));
+
+        let mut noisy = PixelStatistics::new();
+        for i in 0..adaptive.min_samples {
+            let value = (i % 2) as f32;
+            noisy.add(&Color::from((value, value, value)));
+        }
+        assert!(!adaptive.converged(&noisy));
+        assert!((noisy.mean - 0.5).abs() < 1.0e-6);
+    }
+}
@@ -0,0 +1,317 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use image::{ImageError, ImageResult, Rgb, RgbImage};
+use uuid::Uuid;
+
+use crate::{color::Color, vectors::vector3::Vector3};
+
+use super::framebuffer::{is_exr, FrameBuffer};
+
+/// Arbitrary output variables: render passes output along with the beauty
+/// image, e.g. for compositing.
+pub enum Aov {
+    /// Distance to the first hit along the camera ray (`HitRecord::t`).
+    Depth,
+    /// World space normal at the first hit.
+    Normal,
+    /// Attenuation of the first scattering. White where the path ends
+    /// without scattering (background, lights).
+    Albedo,
+    /// Color derived from the id of the material at the first hit.
+    MaterialId,
+    /// Color derived from the id of the object at the first hit.
+    ObjectId,
+    /// Light reflected diffusely at the first hit, coming straight from an
+    /// emitter or the background.
+    DirectDiffuse,
+    /// Light reflected diffusely at the first hit after further bounces.
+    IndirectDiffuse,
+    /// Like `DirectDiffuse` for specular surfaces (mirrors, glass).
+    DirectSpecular,
+    /// Like `IndirectDiffuse` for specular surfaces (mirrors, glass).
+    IndirectSpecular,
+    /// Number of samples taken for the pixel. Shown as a heatmap in 8-bit
+    /// images.
+    SampleCount,
+}
+
+impl Copy for Aov {}
+impl Clone for Aov {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl PartialEq for Aov {
+    fn eq(&self, other: &Self) -> bool {
+        *self as u8 == *other as u8
+    }
+}
+
+impl Aov {
+    pub const ALL: [Aov; 10] = [
+        Aov::Depth,
+        Aov::Normal,
+        Aov::Albedo,
+        Aov::MaterialId,
+        Aov::ObjectId,
+        Aov::DirectDiffuse,
+        Aov::IndirectDiffuse,
+        Aov::DirectSpecular,
+        Aov::IndirectSpecular,
+        Aov::SampleCount,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Aov::Depth => "depth",
+            Aov::Normal => "normal",
+            Aov::Albedo => "albedo",
+            Aov::MaterialId => "material_id",
+            Aov::ObjectId => "object_id",
+            Aov::DirectDiffuse => "direct_diffuse",
+            Aov::IndirectDiffuse => "indirect_diffuse",
+            Aov::DirectSpecular => "direct_specular",
+            Aov::IndirectSpecular => "indirect_specular",
+            Aov::SampleCount => "sample_count",
+        }
+    }
+
+    /// Channels of the pass in an EXR file. Scalar passes have one channel.
+    fn channels(self) -> &'static [&'static str] {
+        match self {
+            Aov::Depth => &["Z"],
+            Aov::SampleCount => &["Y"],
+            _ => &["R", "G", "B"],
+        }
+    }
+
+    /// Whether the pass holds light, shown gamma corrected like the beauty
+    /// image, rather than data.
+    fn is_light(self) -> bool {
+        matches!(
+            self,
+            Aov::DirectDiffuse | Aov::IndirectDiffuse | Aov::DirectSpecular | Aov::IndirectSpecular
+        )
+    }
+}
+
+/// Surface seen by a camera sample.
+pub struct FirstHit {
+    pub depth: f32,
+    pub normal: Vector3,
+    pub albedo: Color,
+    pub material_id: Uuid,
+    pub object_id: Uuid,
+}
+
+/// Radiance of one camera sample with the values of the render passes.
+pub struct PathSample {
+    pub color: Color,
+    /// None if the camera ray escaped to the background.
+    pub first_hit: Option<FirstHit>,
+    pub direct_diffuse: Color,
+    pub indirect_diffuse: Color,
+    pub direct_specular: Color,
+    pub indirect_specular: Color,
+}
+
+impl PathSample {
+    pub fn background(color: Color) -> Self {
+        PathSample {
+            color,
+            first_hit: None,
+            direct_diffuse: Color::zero(),
+            indirect_diffuse: Color::zero(),
+            direct_specular: Color::zero(),
+            indirect_specular: Color::zero(),
+        }
+    }
+}
+
+/// Color identifying an id in the id passes.
+pub fn id_color(id: &Uuid) -> Color {
+    let bytes = id.as_bytes();
+    Color::from((
+        bytes[0] as f32 / 255.0,
+        bytes[1] as f32 / 255.0,
+        bytes[2] as f32 / 255.0,
+    ))
+}
+
+/// Largest component in the buffer, to normalize scalar passes for display.
+fn max_value(buffer: &FrameBuffer) -> f32 {
+    buffer
+        .pixels
+        .iter()
+        .map(|color| color.x.max(color.y).max(color.z))
+        .fold(0.0, f32::max)
+}
+
+/// Blue (0) to red (1) through green and yellow.
+fn heat_color(t: f32) -> Color {
+    let t = t.clamp(0.0, 1.0);
+    Color::from((
+        (2.0 * t - 0.5).clamp(0.0, 1.0),
+        1.0 - (2.0 * t - 1.0).abs(),
+        (1.0 - 2.0 * t).clamp(0.0, 1.0),
+    ))
+}
+
+/// 8-bit image of a data pass, mapped linearly into the displayable range.
+fn to_data_image(aov: Aov, buffer: &FrameBuffer) -> RgbImage {
+    let display = match aov {
+        Aov::Normal => buffer.map(|normal| normal * 0.5 + 0.5),
+        Aov::Depth => {
+            let max = max_value(buffer);
+            if max > 0.0 {
+                buffer.map(|value| value / max)
+            } else {
+                buffer.map(|value| value)
+            }
+        }
+        Aov::SampleCount => {
+            let max = max_value(buffer).max(1.0);
+            buffer.map(|count| heat_color(count.x / max))
+        }
+        _ => buffer.map(|value| value),
+    };
+    RgbImage::from_fn(buffer.width, buffer.height, |x, y| {
+        let color = display.get(x, y);
+        Rgb([color.x, color.y, color.z].map(|value| (value.clamp(0.0, 1.0) * 255.0).round() as u8))
+    })
+}
+
+/// Beauty image and the requested render passes.
+pub struct RenderPasses {
+    pub beauty: FrameBuffer,
+    pub aovs: Vec<(Aov, FrameBuffer)>,
+    /// Beauty image of the even and of the odd samples, for estimating the
+    /// variance (e.g. for denoising).
+    pub halves: Option<(FrameBuffer, FrameBuffer)>,
+}
+
+impl RenderPasses {
+    pub fn get(&self, aov: Aov) -> Option<&FrameBuffer> {
+        self.aovs
+            .iter()
+            .find(|(a, _)| *a == aov)
+            .map(|(_, buffer)| buffer)
+    }
+
+    /// Path of the image of a pass next to the beauty image, e.g.
+    /// `render.depth.png` for `render.png`.
+    pub fn layer_path(path: &Path, aov: Aov) -> PathBuf {
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let file_name = match path.extension() {
+            Some(extension) => format!("{}.{}.{}", stem, aov.name(), extension.to_string_lossy()),
+            None => format!("{}.{}", stem, aov.name()),
+        };
+        path.with_file_name(file_name)
+    }
+
+    /// Save the beauty image to `path` and each pass as its own image (see
+    /// `layer_path`), mapped to 8 bits for viewing. EXR paths get one
+    /// multi-layer file with the raw values instead, see `save_exr`.
+    pub fn save_images(&self, path: &Path) -> ImageResult<()> {
+        if is_exr(path) {
+            return self
+                .save_exr(path)
+                .map_err(|error| ImageError::IoError(io::Error::other(error)));
+        }
+        self.beauty.save(path)?;
+        for (aov, buffer) in &self.aovs {
+            let layer_path = RenderPasses::layer_path(path, *aov);
+            if aov.is_light() {
+                buffer.save(&layer_path)?;
+            } else {
+                to_data_image(*aov, buffer).save(&layer_path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Save the beauty image and all passes as layers of one multi-layer
+    /// OpenEXR file.
+    pub fn save_exr(&self, path: &Path) -> exr::error::UnitResult {
+        use exr::prelude::*;
+
+        let size = Vec2(self.beauty.width as usize, self.beauty.height as usize);
+        let layer = |name: &str, channels: &[&str], buffer: &FrameBuffer| {
+            let channels = channels
+                .iter()
+                .enumerate()
+                .map(|(i, channel)| {
+                    let samples = buffer.pixels.iter().map(|color| color[i]).collect();
+                    AnyChannel::new(*channel, FlatSamples::F32(samples))
+                })
+                .collect();
+            Layer::new(
+                size,
+                LayerAttributes::named(name),
+                Encoding::FAST_LOSSLESS,
+                AnyChannels::sort(channels),
+            )
+        };
+
+        let mut layers = vec![layer("beauty", &["R", "G", "B"], &self.beauty)];
+        for (aov, buffer) in &self.aovs {
+            layers.push(layer(aov.name(), aov.channels(), buffer));
+        }
+        let image = Image::from_layers(
+            ImageAttributes::new(IntegerBounds::from_dimensions(size)),
+            layers,
+        );
+        image.write().to_file(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_write_passes_as_exr_layers() {
+        let mut beauty = FrameBuffer::new(3, 2);
+        beauty.set(2, 1, Color::from((0.25, 0.5, 4.0)));
+        let mut depth = FrameBuffer::new(3, 2);
+        depth.set(1, 0, Color::from((7.5, 7.5, 7.5)));
+        let passes = RenderPasses {
+            beauty,
+            aovs: vec![(Aov::Depth, depth)],
+            halves: None,
+        };
+
+        let path = std::env::temp_dir().join(format!("passes-{}.exr", std::process::id()));
+        passes.save_images(&path).unwrap();
+        let image = exr::prelude::read_all_flat_layers_from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let channel = |layer: &str, channel: &str, index: usize| {
+            let layer = image
+                .layer_data
+                .iter()
+                .find(|l| {
+                    l.attributes
+                        .layer_name
+                        .as_ref()
+                        .is_some_and(|n| n.eq(layer))
+                })
+                .unwrap();
+            let channel = layer
+                .channel_data
+                .list
+                .iter()
+                .find(|c| c.name.eq(channel))
+                .unwrap();
+            channel.sample_data.value_by_flat_index(index).to_f32()
+        };
+        assert_eq!(image.layer_data.len(), 2);
+        assert_eq!(channel("beauty", "B", 5), 4.0);
+        assert_eq!(channel("beauty", "G", 5), 0.5);
+        assert_eq!(channel("depth", "Z", 1), 7.5);
+        assert_eq!(channel("depth", "Z", 0), 0.0);
+    }
+}
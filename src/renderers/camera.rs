@@ -1,27 +1,40 @@
-use std::sync::mpsc;
+use std::{
+    ops::Range,
+    sync::mpsc,
+    time::{Duration, Instant},
+};
 
-use image::RgbImage;
-use log::debug;
-use rand::random;
+use log::{debug, info, warn};
 use threadpool::ThreadPool;
 
 use crate::{
-    color::{get_rgb, Color},
+    color::Color,
     geometry::{
         axis::{Axes2D, Axes3D},
         coordinate::CoordinateSystem,
     },
     interval::Interval,
     objects::hittable::Hittable,
-    optical::ray::Ray,
-    vectors::{
-        ops::MatrixCross,
-        vector3::{Point3, Vector3},
-    },
+    optical::{aperture::Aperture, ray::Ray},
+    samplers::sampler::{sample_1d, with_sampler, Sampler},
+    spectrum::sampled::{SampledSpectrum, SampledWavelengths},
+    vectors::vector3::{Point3, Vector3},
     world::World,
 };
 
-use super::renderer::Renderer;
+use super::{
+    accumulator::Accumulator,
+    adaptive::AdaptiveSampling,
+    aov::{Aov, FirstHit, PathSample, RenderPasses},
+    camera_model::{view_axes, CameraModel},
+    checkpoint::{load_checkpoint, save_checkpoint, Checkpoint, RenderSettings},
+    denoiser::Denoiser,
+    filter::Filter,
+    progress::{CancellationToken, Progress, ProgressObserver},
+    progressive::Progressive,
+    renderer::Renderer,
+    statistics::{self, Statistics, StatisticsReport},
+};
 
 fn ray_color_background(r: &Ray) -> Color {
     let dir = r.direction.to_unit();
@@ -29,36 +42,202 @@ fn ray_color_background(r: &Ray) -> Color {
     Color::from((1.0, 1.0, 1.0)) * (1.0 - alpha) + Color::from((0.5, 0.7, 1.0)) * alpha
 }
 
-fn ray_color(ray: &Ray, world: &World, depth: i32) -> Color {
+/// Offset of the ray interval to avoid self-intersection ("shadow acne")
+/// of rays scattered at a surface.
+const SURFACE_T_MIN: f32 = 0.001;
+
+/// Radiance along the ray, split into the light emitted at its first hit
+/// (or the background) and the light scattered there.
+fn ray_color_split(ray: &Ray, world: &World, depth: i32, t_min: f32) -> (Color, Color) {
     if depth <= 0 {
-        return Color::from((0.0, 0.0, 0.0));
+        return (Color::zero(), Color::zero());
     }
 
     let World { objects, materials } = world;
-    match objects.hit(&ray, Interval::from((0.001, f32::INFINITY))) {
+    statistics::record(|statistics| statistics.secondary_rays += 1);
+    match objects.hit(&ray, Interval::from((t_min, f32::INFINITY))) {
         Some(result) => {
-            let id = result.material_id.clone();
-            let material = &materials.materials[&id].material;
+            let material = &materials[result.material_id].material;
+            // Rays scattered inside of a medium do not leave a surface, so
+            // they must not skip anything close to the scattering point
+            // (e.g. the boundary of a thin volume).
+            let next_t_min = if material.is_volumetric() {
+                0.0
+            } else {
+                SURFACE_T_MIN
+            };
+            let emitted = material.emitted(ray, &result);
             match material.scatter(ray, &result) {
-                Some(scattered) => {
-                    ray_color(&scattered.ray, world, depth - 1) * scattered.attenuation
+                Some(scattered) => (
+                    emitted,
+                    ray_color(&scattered.ray, world, depth - 1, next_t_min) * scattered.attenuation,
+                ),
+                None => (emitted, Color::zero()),
+            }
+        }
+        None => (ray_color_background(&ray), Color::zero()),
+    }
+}
+
+fn ray_color(ray: &Ray, world: &World, depth: i32, t_min: f32) -> Color {
+    let (emitted, scattered) = ray_color_split(ray, world, depth, t_min);
+    emitted + scattered
+}
+
+/// `ray_color_split` carrying sampled wavelengths.
+fn ray_color_spectral_split(
+    ray: &Ray,
+    world: &World,
+    depth: i32,
+    t_min: f32,
+    wavelengths: &mut SampledWavelengths,
+) -> (SampledSpectrum, SampledSpectrum) {
+    if depth <= 0 {
+        return (
+            SampledSpectrum::constant(0.0),
+            SampledSpectrum::constant(0.0),
+        );
+    }
+
+    let World { objects, materials } = world;
+    statistics::record(|statistics| statistics.secondary_rays += 1);
+    match objects.hit(ray, Interval::from((t_min, f32::INFINITY))) {
+        Some(result) => {
+            let material = &materials[result.material_id].material;
+            let next_t_min = if material.is_volumetric() {
+                0.0
+            } else {
+                SURFACE_T_MIN
+            };
+            let emitted = SampledSpectrum::from_rgb(&material.emitted(ray, &result), wavelengths);
+            match material.scatter_spectral(ray, &result, wavelengths) {
+                Some(scattered) => (
+                    emitted,
+                    ray_color_spectral(&scattered.ray, world, depth - 1, next_t_min, wavelengths)
+                        * scattered.attenuation,
+                ),
+                None => (emitted, SampledSpectrum::constant(0.0)),
+            }
+        }
+        None => (
+            SampledSpectrum::from_rgb(&ray_color_background(ray), wavelengths),
+            SampledSpectrum::constant(0.0),
+        ),
+    }
+}
+
+fn ray_color_spectral(
+    ray: &Ray,
+    world: &World,
+    depth: i32,
+    t_min: f32,
+    wavelengths: &mut SampledWavelengths,
+) -> SampledSpectrum {
+    let (emitted, scattered) = ray_color_spectral_split(ray, world, depth, t_min, wavelengths);
+    emitted + scattered
+}
+
+/// Light reaching the camera after the first scattering: arriving directly
+/// from an emitter (or the background), and after further bounces.
+struct FirstScatter {
+    attenuation: Color,
+    direct: Color,
+    indirect: Color,
+}
+
+/// Trace one camera ray, either in RGB or carrying sampled wavelengths, and
+/// record the render passes at its first hit.
+fn trace_sample(ray: &Ray, world: &World, max_depth: i32, spectral: bool) -> PathSample {
+    if max_depth <= 0 {
+        return PathSample::background(Color::zero());
+    }
+    let World { objects, materials } = world;
+    statistics::record(|statistics| statistics.primary_rays += 1);
+    let Some((object_id, result)) =
+        objects.hit_object(ray, Interval::from((SURFACE_T_MIN, f32::INFINITY)))
+    else {
+        return PathSample::background(ray_color_background(ray));
+    };
+
+    let material = &materials[result.material_id].material;
+    let next_t_min = if material.is_volumetric() {
+        0.0
+    } else {
+        SURFACE_T_MIN
+    };
+    let (emitted, first_scatter) = if spectral {
+        let mut wavelengths = SampledWavelengths::sample_uniform(sample_1d());
+        let emitted = SampledSpectrum::from_rgb(&material.emitted(ray, &result), &wavelengths);
+        let first_scatter = material
+            .scatter_spectral(ray, &result, &mut wavelengths)
+            .map(|scattered| {
+                let (direct, indirect) = ray_color_spectral_split(
+                    &scattered.ray,
+                    world,
+                    max_depth - 1,
+                    next_t_min,
+                    &mut wavelengths,
+                );
+                FirstScatter {
+                    attenuation: scattered.attenuation.to_rgb(&wavelengths),
+                    direct: (direct * scattered.attenuation).to_rgb(&wavelengths),
+                    indirect: (indirect * scattered.attenuation).to_rgb(&wavelengths),
                 }
-                None => Color::from((0.0, 0.0, 0.0)),
+            });
+        (emitted.to_rgb(&wavelengths), first_scatter)
+    } else {
+        let emitted = material.emitted(ray, &result);
+        let first_scatter = material.scatter(ray, &result).map(|scattered| {
+            let (direct, indirect) =
+                ray_color_split(&scattered.ray, world, max_depth - 1, next_t_min);
+            FirstScatter {
+                attenuation: scattered.attenuation,
+                direct: direct * scattered.attenuation,
+                indirect: indirect * scattered.attenuation,
             }
+        });
+        (emitted, first_scatter)
+    };
+
+    let mut sample = PathSample::background(emitted);
+    let mut albedo = Color::from((1.0, 1.0, 1.0));
+    if let Some(FirstScatter {
+        attenuation,
+        direct,
+        indirect,
+    }) = first_scatter
+    {
+        sample.color += direct + indirect;
+        albedo = attenuation;
+        if material.is_specular() {
+            sample.direct_specular = direct;
+            sample.indirect_specular = indirect;
+        } else {
+            sample.direct_diffuse = direct;
+            sample.indirect_diffuse = indirect;
         }
-        None => ray_color_background(&ray),
     }
+    sample.first_hit = Some(FirstHit {
+        depth: result.t,
+        normal: result.norm,
+        albedo,
+        material_id: result.material_id,
+        object_id,
+    });
+    sample
 }
 
-/// Returns a random point in the square surrounding a pixel at the origin.
-fn pixel_sample_square(axes: Axes3D) -> Vector3 {
-    let px = -0.5 + random::<f32>();
-    let py = -0.5 + random::<f32>();
+/// Returns a point in the square surrounding a pixel at the origin for the
+/// sample `u`.
+fn pixel_sample_square(axes: Axes3D, u: (f32, f32)) -> Vector3 {
+    let px = -0.5 + u.0;
+    let py = -0.5 + u.1;
     (axes.u * px) + (axes.v * py)
 }
-fn defocus_disk_sample(center: Vector3, defocus_disk_axes: Axes2D) -> Point3 {
-    let p = Vector3::<f32>::random_in_unit_disk();
-    center + (defocus_disk_axes.u * p.x) + (defocus_disk_axes.v * p.y)
+/// Returns the point of the defocus disk at the aperture point `p`, see
+/// `Aperture::sample`.
+fn defocus_disk_sample(center: Vector3, defocus_disk_axes: Axes2D, p: (f32, f32)) -> Point3 {
+    center + (defocus_disk_axes.u * p.0) + (defocus_disk_axes.v * p.1)
 }
 
 pub struct CameraGeometryParam {
@@ -67,10 +246,35 @@ pub struct CameraGeometryParam {
     pub up: Vector3,
 }
 
+/// How the camera maps the scene onto the image.
+pub enum Projection {
+    /// Pinhole or thin lens with the field of view `vfov_deg`.
+    Perspective,
+    /// Parallel rays, e.g. for architectural or technical illustrations.
+    /// Defocus still blurs everything off the focus plane.
+    Orthographic {
+        /// Height of the view in world units.
+        view_height: f32,
+    },
+}
+
+impl Copy for Projection {}
+impl Clone for Projection {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
 pub struct CameraOpticalParam {
+    pub projection: Projection,
     pub focus_dist: f32,
     pub vfov_deg: f32,
     pub defocus_angle: f32,
+    pub aperture: Aperture,
+    /// Cat's-eye vignetting: the lens barrel cuts off the aperture off the
+    /// optical axis, by this shift of the barrel opening (in aperture radii)
+    /// per half image height. 0 disables it.
+    pub cat_eye: f32,
 }
 
 pub struct ImageSize {
@@ -78,6 +282,16 @@ pub struct ImageSize {
     pub width: u32,
 }
 
+impl ImageSize {
+    /// Width and height in pixels, at least one pixel high.
+    pub fn rect(&self) -> Rect<u32> {
+        Rect {
+            width: self.width,
+            height: ((self.width as f32 / self.aspect_ratio) as u32).max(1),
+        }
+    }
+}
+
 pub struct Rect<T: num_traits::Num> {
     pub width: T,
     pub height: T,
@@ -126,39 +340,60 @@ pub struct Camera {
     pub samples_per_pixel: u32,
     pub max_depth: i32,
     pub max_workers: usize,
+    /// Trace wavelengths instead of RGB, e.g. for dispersion.
+    pub spectral: bool,
+    /// Render passes output along with the beauty image.
+    pub aovs: Vec<Aov>,
+    /// Sample each pixel until its estimated error is small enough instead
+    /// of `samples_per_pixel` times.
+    pub adaptive: Option<AdaptiveSampling>,
+    /// Render in passes, writing the image so far regularly.
+    pub progressive: Option<Progressive>,
+    /// Save the samples regularly to resume an interrupted render.
+    pub checkpoint: Option<Checkpoint>,
+    /// Print the render statistics at the end of the render.
+    pub statistics: Option<StatisticsReport>,
+    /// Reconstruction filter weighting the samples into the pixels.
+    pub filter: Filter,
+    /// Source of the samples for pixel jitter, lens and scattering.
+    pub sampler: Box<dyn Sampler>,
+    /// Denoise the beauty image after rendering. Adds the passes it needs.
+    pub denoiser: Option<Denoiser>,
+    /// Render through another camera model, e.g. a panoramic camera,
+    /// instead of the projection of this camera.
+    pub model: Option<Box<dyn CameraModel>>,
 }
 
 impl Camera {
     fn initialize(&self) -> RenderingParameters {
-        let CameraGeometryParam { center, lookat, up } = self.geometry;
+        let center = self.geometry.center;
         let ImageSize {
             aspect_ratio,
             width: image_width,
         } = self.image_size;
 
         let CameraOpticalParam {
+            projection,
             vfov_deg,
             focus_dist,
             defocus_angle,
+            ..
         } = self.optical_params;
 
         let _image_height = (image_width as f32 / aspect_ratio) as u32;
         let image_height = if _image_height >= 1 { _image_height } else { 1 };
 
         // Determine viewport dimensions.
-        let h = (vfov_deg.to_radians() / 2.0).tan();
-        let viewport_height: f32 = 2.0 * h * focus_dist;
+        let viewport_height: f32 = match projection {
+            Projection::Perspective => 2.0 * (vfov_deg.to_radians() / 2.0).tan() * focus_dist,
+            Projection::Orthographic { view_height } => view_height,
+        };
         let viewport_width = viewport_height * (image_width as f32 / image_height as f32);
 
-        let coord_w = (center - lookat).to_unit();
-        let coord_u = up.cross(&coord_w).to_unit();
-        let coord_v = coord_w.cross(&coord_u);
+        let axes = view_axes(&self.geometry);
+        let (coord_u, coord_v, coord_w) = (axes.u, axes.v, axes.w);
         let coordinate = CoordinateSystem {
-            axes: Axes3D {
-                u: coord_u,
-                v: coord_v,
-                w: coord_w,
-            },
+            axes,
             origin: center,
         };
 
@@ -212,60 +447,478 @@ impl Camera {
     }
 
     /// Get a randomly-asmpled camera ray for the pixel at location (x, y),
-    /// originating from the camera defocus disk.
-    fn get_ray(&self, x: u32, y: u32, rendering_params: RenderingParameters) -> Ray {
+    /// originating from the camera defocus disk. Orthographic rays start
+    /// from a lens behind each pixel, in the plane of the camera center.
+    fn thin_lens_ray(
+        &self,
+        x: u32,
+        y: u32,
+        rendering_params: RenderingParameters,
+        pixel_sample: (f32, f32),
+        lens_sample: (f32, f32),
+    ) -> Option<Ray> {
         let image_coord = rendering_params.image_coord;
         let pixel_center =
             image_coord.origin + (image_coord.axes.u * x as f32) + (image_coord.axes.v * y as f32);
-        let pixel_sample = pixel_center + pixel_sample_square(image_coord.axes);
+        let pixel_sample = pixel_center + pixel_sample_square(image_coord.axes, pixel_sample);
 
-        let ray_origin = if self.optical_params.defocus_angle <= 0.0 {
-            self.geometry.center
+        let lens_center = match self.optical_params.projection {
+            Projection::Perspective => self.geometry.center,
+            Projection::Orthographic { .. } => {
+                let w = rendering_params.camera_coord.axes.w;
+                pixel_sample + w * self.optical_params.focus_dist
+            }
+        };
+        let optical_params = &self.optical_params;
+        let ray_origin = if optical_params.defocus_angle <= 0.0 {
+            lens_center
         } else {
-            defocus_disk_sample(self.geometry.center, rendering_params.defocus_disk_axes)
+            let p = optical_params.aperture.sample(lens_sample);
+            if optical_params.cat_eye > 0.0 {
+                // Vignetted if outside of the barrel opening, shifted
+                // towards the image position of the pixel.
+                let Rect { width, height } = rendering_params.image_rect;
+                let half_height = height as f32 / 2.0;
+                let fx = (x as f32 + 0.5 - width as f32 / 2.0) / half_height;
+                let fy = (half_height - y as f32 - 0.5) / half_height;
+                let (dx, dy) = (
+                    p.0 - optical_params.cat_eye * fx,
+                    p.1 - optical_params.cat_eye * fy,
+                );
+                if dx * dx + dy * dy > 1.0 {
+                    return None;
+                }
+            }
+            defocus_disk_sample(lens_center, rendering_params.defocus_disk_axes, p)
         };
         let ray_direction = (pixel_sample - ray_origin).to_unit();
-        Ray::from((ray_origin, ray_direction))
+        Some(Ray::from((ray_origin, ray_direction)))
+    }
+
+    /// The projection of this camera as a camera model, with its
+    /// parameters computed once.
+    pub fn projection(&self) -> CameraProjection<'_> {
+        CameraProjection {
+            camera: self,
+            render_params: self.initialize(),
+        }
+    }
+}
+
+/// Perspective or orthographic thin lens projection of a `Camera`.
+pub struct CameraProjection<'a> {
+    camera: &'a Camera,
+    render_params: RenderingParameters,
+}
+
+impl CameraModel for CameraProjection<'_> {
+    fn image_rect(&self) -> Rect<u32> {
+        self.render_params.image_rect
+    }
+
+    fn get_ray(
+        &self,
+        x: u32,
+        y: u32,
+        pixel_sample: (f32, f32),
+        lens_sample: (f32, f32),
+    ) -> Option<Ray> {
+        self.camera
+            .thin_lens_ray(x, y, self.render_params, pixel_sample, lens_sample)
+    }
+}
+
+impl Camera {
+    /// Trace samples of pixel (x, y) until it has `target` samples, or
+    /// adaptive sampling finds it converged. Returns the counters of the
+    /// samples traced.
+    fn sample_pixel(
+        &self,
+        world: &'static World,
+        thread_pool: &ThreadPool,
+        model: &dyn CameraModel,
+        accumulator: &mut Accumulator,
+        (x, y): (u32, u32),
+        target: u32,
+    ) -> Statistics {
+        let mut statistics = Statistics::new();
+        loop {
+            let count = accumulator.sample_count(x, y);
+            let converged = match &self.adaptive {
+                Some(adaptive) => adaptive.converged(accumulator.pixel_statistics(x, y)),
+                None => false,
+            };
+            if count >= target || converged {
+                return statistics;
+            }
+            let batch = match &self.adaptive {
+                Some(adaptive) if count < adaptive.min_samples => adaptive.min_samples - count,
+                Some(adaptive) => adaptive.batch_size.max(1),
+                None => target - count,
+            }
+            .min(target - count);
+
+            let max_depth = self.max_depth;
+            let spectral = self.spectral;
+            let (tx, rx) = mpsc::channel::<((f32, f32), PathSample, Statistics)>();
+            for i in 0..batch {
+                let tx = tx.clone();
+                let mut sampler = self.sampler.clone_box();
+                sampler.start_pixel_sample((x, y), count + i);
+                let pixel_sample = sampler.get_2d();
+                let lens_sample = sampler.get_2d();
+                let ray = model.get_ray(x, y, pixel_sample, lens_sample);
+                let offset = (pixel_sample.0 - 0.5, pixel_sample.1 - 0.5);
+
+                thread_pool.execute(move || {
+                    statistics::take();
+                    let sample = match ray {
+                        Some(ray) => {
+                            with_sampler(sampler, || trace_sample(&ray, world, max_depth, spectral))
+                        }
+                        None => PathSample::background(Color::zero()),
+                    };
+                    tx.send((offset, sample, statistics::take())).unwrap();
+                });
+            }
+            thread_pool.join();
+            for (offset, sample, counters) in rx.iter().take(batch as usize) {
+                accumulator.add(&self.filter, x, y, offset, &sample);
+                statistics.merge(&counters);
+            }
+        }
+    }
+
+    /// Write the image of the samples so far.
+    fn write_progress(&self, progressive: &Progressive, accumulator: &Accumulator) {
+        if let Err(error) = accumulator.resolve().save_images(&progressive.output) {
+            warn!("\nFailed to write {:?}: {}", progressive.output, error);
+        }
+    }
+
+    /// Settings a checkpoint has to match to be resumed.
+    fn render_settings(&self, max_samples: u32) -> RenderSettings {
+        RenderSettings {
+            max_samples,
+            filter: self.filter.describe(),
+            sampler: self.sampler.describe(),
+            spectral: self.spectral,
+        }
+    }
+
+    fn write_checkpoint(
+        &self,
+        checkpoint: &Checkpoint,
+        settings: &RenderSettings,
+        accumulator: &Accumulator,
+    ) {
+        if let Err(error) = save_checkpoint(accumulator, settings, &checkpoint.path) {
+            warn!("\nFailed to write {:?}: {}", checkpoint.path, error);
+        }
+    }
+
+    /// Resolve the accumulated samples into the output images.
+    fn finish(&self, accumulator: &Accumulator) -> RenderPasses {
+        let mut passes = accumulator.resolve();
+        if let Some(denoiser) = &self.denoiser {
+            passes.beauty = denoiser.denoise(&passes);
+        }
+        passes
     }
 }
 
+/// Edge length of the square tiles the image is rendered in.
+const TILE_SIZE: u32 = 16;
+
+/// Pixel ranges of the tiles covering the image, row by row.
+fn tiles(width: u32, height: u32) -> Vec<(Range<u32>, Range<u32>)> {
+    let mut tiles = Vec::new();
+    for y in (0..height).step_by(TILE_SIZE as usize) {
+        for x in (0..width).step_by(TILE_SIZE as usize) {
+            tiles.push((
+                x..(x + TILE_SIZE).min(width),
+                y..(y + TILE_SIZE).min(height),
+            ));
+        }
+    }
+    tiles
+}
+
 impl Renderer for Camera {
-    fn render(&self, world: &'static World) -> RgbImage {
-        let render_params = self.initialize();
+    fn render_observed(
+        &self,
+        world: &'static World,
+        observer: &dyn ProgressObserver,
+        cancel: &CancellationToken,
+    ) -> RenderPasses {
+        let setup = Instant::now();
+        let mut statistics = Statistics::new();
+        let projection = self.projection();
+        let model: &dyn CameraModel = match &self.model {
+            Some(model) => model.as_ref(),
+            None => &projection,
+        };
         let thread_pool = ThreadPool::new(self.max_workers);
 
         let Rect {
             width: image_width,
             height: image_height,
-        } = render_params.image_rect;
-
-        let mut image = RgbImage::new(image_width, image_height);
-
-        for y in 0..image_height {
-            debug!("\rScanlines remaining: {}   ", image_height - y);
-            for x in 0..image_width {
-                let max_depth = self.max_depth;
-                let (tx, rx) = mpsc::channel::<Color>();
-                for _ in 0..self.samples_per_pixel {
-                    let tx = tx.clone();
-                    let ray = self.get_ray(x, y, render_params);
-
-                    thread_pool.execute(move || {
-                        let color = ray_color(&ray, world, max_depth);
-                        tx.send(color).unwrap();
-                    });
+        } = model.image_rect();
+
+        let mut aovs = self.aovs.clone();
+        if self.denoiser.is_some() {
+            for aov in [Aov::Albedo, Aov::Normal] {
+                if !aovs.contains(&aov) {
+                    aovs.push(aov);
+                }
+            }
+        }
+        let max_samples = match &self.adaptive {
+            Some(adaptive) => adaptive.max_samples,
+            None => self.samples_per_pixel,
+        };
+        let settings = self.render_settings(max_samples);
+        let mut accumulator =
+            Accumulator::new(&aovs, image_width, image_height, self.denoiser.is_some());
+        let checkpoint = self.checkpoint.as_ref().filter(|checkpoint| {
+            let deterministic = self.sampler.is_deterministic();
+            if !deterministic {
+                warn!(
+                    "\nIgnoring checkpoint {:?}: the {} sampler cannot resume its samples",
+                    checkpoint.path,
+                    self.sampler.describe()
+                );
+            }
+            deterministic
+        });
+        if let Some(checkpoint) = checkpoint {
+            if checkpoint.resume && checkpoint.path.exists() {
+                match load_checkpoint(&checkpoint.path, &settings, image_width, image_height) {
+                    Ok(resumed) if resumed.matches(&accumulator) => {
+                        debug!("\rResuming from {:?}\n", checkpoint.path);
+                        accumulator = resumed;
+                    }
+                    Ok(_) => warn!("\nCheckpoint {:?} is of another render", checkpoint.path),
+                    Err(error) => warn!("\nFailed to read {:?}: {}", checkpoint.path, error),
+                }
+            }
+        }
+
+        let pass_targets = match &self.progressive {
+            Some(progressive) => progressive.pass_targets(max_samples),
+            None => vec![max_samples],
+        };
+        let stopped =
+            || cancel.is_cancelled() || self.progressive.as_ref().is_some_and(|p| p.stopped());
+        let tiles = tiles(image_width, image_height);
+        let start = Instant::now();
+        let mut progress = Progress {
+            pass: 0,
+            passes: pass_targets.len() as u32,
+            tiles_done: 0,
+            tiles_total: (tiles.len() * pass_targets.len()) as u32,
+            samples: 0,
+            rays: 0,
+            elapsed: Default::default(),
+            fraction: 0.0,
+        };
+        // Sample budget of the whole render and of the tiles done, to
+        // estimate the share done with passes of growing sample counts.
+        let pixels = |(xs, ys): &(Range<u32>, Range<u32>)| (xs.len() * ys.len()) as f32;
+        let budget = (image_width * image_height) as f32 * max_samples as f32;
+        let mut spent = 0.0;
+        let mut last_write = Instant::now();
+        let mut last_checkpoint = Instant::now();
+        let mut writing = Duration::ZERO;
+        statistics.add_phase("setup", setup.elapsed());
+
+        'passes: for (pass, target) in pass_targets.iter().enumerate() {
+            let previous_target = pass.checked_sub(1).map_or(0, |i| pass_targets[i]);
+            progress.pass = pass as u32 + 1;
+            for tile in &tiles {
+                for y in tile.1.clone() {
+                    for x in tile.0.clone() {
+                        if stopped() {
+                            break 'passes;
+                        }
+                        let count = accumulator.sample_count(x, y);
+                        let counters = self.sample_pixel(
+                            world,
+                            &thread_pool,
+                            model,
+                            &mut accumulator,
+                            (x, y),
+                            *target,
+                        );
+                        statistics.merge(&counters);
+                        progress.samples += (accumulator.sample_count(x, y) - count) as u64;
+                    }
+                }
+                spent += pixels(tile) * (target - previous_target) as f32;
+                progress.tiles_done += 1;
+                progress.elapsed = start.elapsed();
+                progress.fraction = spent / budget.max(1.0);
+                progress.rays = statistics.rays();
+                observer.progress(&progress);
+
+                let write = Instant::now();
+                if let Some(checkpoint) = checkpoint {
+                    if last_checkpoint.elapsed() >= checkpoint.every {
+                        self.write_checkpoint(checkpoint, &settings, &accumulator);
+                        last_checkpoint = Instant::now();
+                    }
+                }
+                if let Some(progressive) = &self.progressive {
+                    let due = progressive
+                        .write_every
+                        .is_some_and(|every| last_write.elapsed() >= every);
+                    if due {
+                        self.write_progress(progressive, &accumulator);
+                        last_write = Instant::now();
+                    }
                 }
-                thread_pool.join();
-                let mut color: Color = Color::from((0.0, 0.0, 0.0));
-                for col in rx.iter().take(self.samples_per_pixel as usize) {
-                    color += col;
+                writing += write.elapsed();
+            }
+            if let Some(progressive) = &self.progressive {
+                let every = progressive.write_every_passes;
+                if every > 0 && (pass as u32 + 1).is_multiple_of(every) {
+                    let write = Instant::now();
+                    self.write_progress(progressive, &accumulator);
+                    last_write = Instant::now();
+                    writing += write.elapsed();
                 }
-                color /= self.samples_per_pixel as f32;
+            }
+        }
+        progress.elapsed = start.elapsed();
+        observer.finished(&progress);
+        statistics.add_phase("sampling", progress.elapsed.saturating_sub(writing));
+
+        let write = Instant::now();
+        if let Some(checkpoint) = checkpoint {
+            self.write_checkpoint(checkpoint, &settings, &accumulator);
+        }
+        writing += write.elapsed();
+
+        let resolve = Instant::now();
+        let passes = self.finish(&accumulator);
+        statistics.add_phase("resolve", resolve.elapsed());
 
-                image.put_pixel(x, y, get_rgb(&color));
+        let write = Instant::now();
+        if let Some(progressive) = &self.progressive {
+            if let Err(error) = passes.save_images(&progressive.output) {
+                warn!("\nFailed to write {:?}: {}", progressive.output, error);
             }
         }
-        debug!("\rDone.                 \n");
-        image
+        writing += write.elapsed();
+        statistics.add_phase("writing", writing);
+
+        match &self.statistics {
+            Some(StatisticsReport::Text) => info!("{}", statistics.report()),
+            Some(StatisticsReport::Json) => info!("{}\n", statistics.to_json()),
+            None => {}
+        }
+        passes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::samplers::independent::IndependentSampler;
+
+    fn camera(image_size: ImageSize, optical_params: CameraOpticalParam) -> Camera {
+        Camera {
+            geometry: CameraGeometryParam {
+                center: Point3::from((0.0, 0.0, 5.0)),
+                lookat: Point3::zero(),
+                up: Vector3::from((0.0, 1.0, 0.0)),
+            },
+            image_size,
+            optical_params,
+            samples_per_pixel: 1,
+            max_depth: 1,
+            max_workers: 1,
+            spectral: false,
+            aovs: Vec::new(),
+            adaptive: None,
+            progressive: None,
+            checkpoint: None,
+            statistics: None,
+            filter: Filter::default(),
+            sampler: Box::new(IndependentSampler),
+            denoiser: None,
+            model: None,
+        }
+    }
+
+    #[test]
+    fn should_cast_parallel_orthographic_rays() {
+        let camera = camera(
+            ImageSize {
+                aspect_ratio: 2.0,
+                width: 32,
+            },
+            CameraOpticalParam {
+                projection: Projection::Orthographic { view_height: 4.0 },
+                focus_dist: 5.0,
+                vfov_deg: 40.0,
+                aperture: Aperture::Disk,
+                cat_eye: 0.0,
+                defocus_angle: 0.0,
+            },
+        );
+        let projection = camera.projection();
+        let rays: Vec<Ray> = [(0, 0), (31, 0), (0, 15), (31, 15), (16, 8)]
+            .into_iter()
+            .map(|(x, y)| projection.get_ray(x, y, (0.5, 0.5), (0.5, 0.5)).unwrap())
+            .collect();
+
+        for ray in &rays {
+            let direction = ray.direction.to_unit();
+            assert!((direction.z + 1.0).abs() < 1e-5, "{}", direction.z);
+            assert!((ray.origin.z - 5.0).abs() < 1e-4);
+        }
+        // pixel centers span the view height less one pixel
+        let spread = rays[0].origin.y - rays[2].origin.y;
+        assert!((spread - 4.0 * 15.0 / 16.0).abs() < 1e-4, "{}", spread);
+    }
+
+    #[test]
+    fn should_vignette_the_lens_towards_the_image_corners() {
+        let optical_params = |cat_eye| CameraOpticalParam {
+            projection: Projection::Perspective,
+            focus_dist: 5.0,
+            vfov_deg: 40.0,
+            aperture: Aperture::Disk,
+            cat_eye,
+            defocus_angle: 10.0,
+        };
+        let image_size = || ImageSize {
+            aspect_ratio: 1.0,
+            width: 33,
+        };
+        // fraction of a grid of lens samples that is not vignetted
+        let open = |camera: &Camera, x: u32, y: u32| {
+            let projection = camera.projection();
+            let n = 16;
+            let mut open = 0;
+            for i in 0..n {
+                for j in 0..n {
+                    let lens_sample = ((i as f32 + 0.5) / n as f32, (j as f32 + 0.5) / n as f32);
+                    if projection.get_ray(x, y, (0.5, 0.5), lens_sample).is_some() {
+                        open += 1;
+                    }
+                }
+            }
+            open as f32 / (n * n) as f32
+        };
+
+        let vignetted = camera(image_size(), optical_params(0.5));
+        assert_eq!(open(&vignetted, 16, 16), 1.0);
+        let corner = open(&vignetted, 0, 0);
+        assert!(corner > 0.0 && corner < 0.8, "{}", corner);
+
+        let unvignetted = camera(image_size(), optical_params(0.0));
+        assert_eq!(open(&unvignetted, 0, 0), 1.0);
     }
 }
@@ -0,0 +1,74 @@
+use crate::{
+    geometry::axis::Axes3D,
+    optical::ray::Ray,
+    vectors::{
+        ops::MatrixCross,
+        vector3::{Point3, Vector3},
+    },
+};
+
+use super::camera::{CameraGeometryParam, Rect};
+
+/// Maps the pixels of the image to camera rays. Implemented by the thin
+/// lens projection of a `Camera` (see `Camera::projection`) and by the
+/// panoramic cameras.
+pub trait CameraModel {
+    /// Size of the image in pixels.
+    fn image_rect(&self) -> Rect<u32>;
+
+    /// Camera ray through the point `pixel_sample` in [0, 1)^2 of the pixel
+    /// (x, y), using `lens_sample` on the aperture. None if the pixel sees
+    /// nothing, e.g. outside of a fisheye circle.
+    fn get_ray(
+        &self,
+        x: u32,
+        y: u32,
+        pixel_sample: (f32, f32),
+        lens_sample: (f32, f32),
+    ) -> Option<Ray>;
+}
+
+/// Orthonormal camera basis: `u` to the right, `v` up and `w` pointing
+/// backwards, away from `lookat`.
+pub fn view_axes(geometry: &CameraGeometryParam) -> Axes3D {
+    let w = (geometry.center - geometry.lookat).to_unit();
+    let u = geometry.up.cross(&w).to_unit();
+    let v = w.cross(&u);
+    Axes3D { u, v, w }
+}
+
+/// Camera center with its view axes, computed once from the geometry.
+pub struct CameraView {
+    pub center: Point3,
+    pub axes: Axes3D,
+}
+
+impl From<CameraGeometryParam> for CameraView {
+    fn from(geometry: CameraGeometryParam) -> Self {
+        CameraView {
+            center: geometry.center,
+            axes: view_axes(&geometry),
+        }
+    }
+}
+
+impl CameraView {
+    /// Direction in camera space (x to the right, y up, looking down -z)
+    /// in world space.
+    pub fn to_world(&self, direction: Vector3) -> Vector3 {
+        self.axes.u * direction.x + self.axes.v * direction.y + self.axes.w * direction.z
+    }
+
+    /// Ray from the camera center along a direction in camera space.
+    pub fn ray(&self, direction: Vector3) -> Ray {
+        Ray::from((self.center, self.to_world(direction).to_unit()))
+    }
+}
+
+/// Position of the sample in the image, in [0, 1]^2 from the upper left.
+pub fn image_uv(rect: Rect<u32>, x: u32, y: u32, pixel_sample: (f32, f32)) -> (f32, f32) {
+    (
+        (x as f32 + pixel_sample.0) / rect.width as f32,
+        (y as f32 + pixel_sample.1) / rect.height as f32,
+    )
+}
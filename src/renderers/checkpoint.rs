@@ -0,0 +1,297 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::color::Color;
+
+use super::{
+    accumulator::Accumulator, adaptive::PixelStatistics, aov::Aov, framebuffer::FrameBuffer,
+};
+
+const MAGIC: &[u8; 4] = b"RTCK";
+const VERSION: u32 = 2;
+/// Upper bounds of the header fields, so a corrupt file fails to load
+/// instead of allocating huge buffers.
+const MAX_PIXELS: u64 = 1 << 28;
+const MAX_STRING_LENGTH: u32 = 4096;
+
+/// Save the accumulated samples of a render regularly, so a killed render
+/// can resume from the file with the same scene and settings.
+///
+/// Resuming restores the sample counts, not any random number state. The
+/// seeded samplers derive every sample from the pixel, the sample index and
+/// the dimension, so they continue their sequences where they stopped.
+/// `IndependentSampler` draws unseeded random numbers, so renders with it
+/// do not write or resume checkpoints.
+pub struct Checkpoint {
+    pub path: PathBuf,
+    pub every: Duration,
+    /// Continue from the file if it exists and matches the render.
+    pub resume: bool,
+}
+
+impl Checkpoint {
+    pub fn new(path: PathBuf) -> Self {
+        Checkpoint {
+            path,
+            every: Duration::from_secs(300),
+            resume: true,
+        }
+    }
+}
+
+/// Settings a checkpoint was rendered with. Resuming with other settings
+/// would mix samples of different images.
+pub struct RenderSettings {
+    pub max_samples: u32,
+    pub filter: String,
+    pub sampler: String,
+    pub spectral: bool,
+}
+
+impl RenderSettings {
+    pub fn matches(&self, other: &RenderSettings) -> bool {
+        self.max_samples == other.max_samples
+            && self.filter == other.filter
+            && self.sampler == other.sampler
+            && self.spectral == other.spectral
+    }
+}
+
+fn write_u32(writer: &mut impl Write, value: u32) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+fn write_f32(writer: &mut impl Write, value: f32) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+fn write_string(writer: &mut impl Write, value: &str) -> io::Result<()> {
+    write_u32(writer, value.len() as u32)?;
+    writer.write_all(value.as_bytes())
+}
+
+fn write_buffer(writer: &mut impl Write, buffer: &FrameBuffer) -> io::Result<()> {
+    for color in &buffer.pixels {
+        for channel in 0..3 {
+            write_f32(writer, color[channel])?;
+        }
+    }
+    Ok(())
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_f32(reader: &mut impl Read) -> io::Result<f32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(f32::from_le_bytes(bytes))
+}
+
+fn read_string(reader: &mut impl Read) -> io::Result<String> {
+    let length = read_u32(reader)?;
+    if length > MAX_STRING_LENGTH {
+        return Err(invalid_data("string too long"));
+    }
+    let mut bytes = vec![0; length as usize];
+    reader.read_exact(&mut bytes)?;
+    String::from_utf8(bytes).map_err(|_| invalid_data("invalid string"))
+}
+
+fn read_buffer(reader: &mut impl Read, buffer: &mut FrameBuffer) -> io::Result<()> {
+    for color in buffer.pixels.iter_mut() {
+        *color = Color::from((read_f32(reader)?, read_f32(reader)?, read_f32(reader)?));
+    }
+    Ok(())
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Write the accumulator to `path`. Writes a temporary file first so an
+/// interrupted write keeps the previous checkpoint.
+pub fn save_checkpoint(
+    accumulator: &Accumulator,
+    settings: &RenderSettings,
+    path: &Path,
+) -> io::Result<()> {
+    let temporary = path.with_extension("tmp");
+    {
+        let mut writer = BufWriter::new(File::create(&temporary)?);
+        writer.write_all(MAGIC)?;
+        write_u32(&mut writer, VERSION)?;
+        write_u32(&mut writer, settings.max_samples)?;
+        write_string(&mut writer, &settings.filter)?;
+        write_string(&mut writer, &settings.sampler)?;
+        write_u32(&mut writer, settings.spectral as u32)?;
+        write_u32(&mut writer, accumulator.width())?;
+        write_u32(&mut writer, accumulator.height())?;
+        write_u32(&mut writer, accumulator.aovs.len() as u32)?;
+        for (aov, _) in &accumulator.aovs {
+            write_u32(&mut writer, *aov as u32)?;
+        }
+        write_u32(&mut writer, accumulator.halves.is_some() as u32)?;
+
+        write_buffer(&mut writer, &accumulator.film.sum)?;
+        for weight in &accumulator.film.weights {
+            write_f32(&mut writer, *weight)?;
+        }
+        for i in 0..accumulator.sample_counts.len() {
+            write_u32(&mut writer, accumulator.sample_counts[i])?;
+            write_u32(&mut writer, accumulator.hit_counts[i])?;
+            let statistics = &accumulator.statistics[i];
+            write_u32(&mut writer, statistics.count)?;
+            write_f32(&mut writer, statistics.mean)?;
+            write_f32(&mut writer, statistics.m2)?;
+        }
+        for (_, buffer) in &accumulator.aovs {
+            write_buffer(&mut writer, buffer)?;
+        }
+        if let Some((even, odd)) = &accumulator.halves {
+            write_buffer(&mut writer, even)?;
+            write_buffer(&mut writer, odd)?;
+        }
+        writer.flush()?;
+    }
+    fs::rename(temporary, path)
+}
+
+/// Read the accumulator written by `save_checkpoint`, if it was rendered
+/// with `expected` settings at the size `width` x `height`.
+pub fn load_checkpoint(
+    path: &Path,
+    expected: &RenderSettings,
+    width: u32,
+    height: u32,
+) -> io::Result<Accumulator> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC || read_u32(&mut reader)? != VERSION {
+        return Err(invalid_data("not a checkpoint of this version"));
+    }
+    let settings = RenderSettings {
+        max_samples: read_u32(&mut reader)?,
+        filter: read_string(&mut reader)?,
+        sampler: read_string(&mut reader)?,
+        spectral: read_u32(&mut reader)? != 0,
+    };
+    if !settings.matches(expected) {
+        return Err(invalid_data("checkpoint of other render settings"));
+    }
+    let size = (read_u32(&mut reader)?, read_u32(&mut reader)?);
+    if size != (width, height) || width as u64 * height as u64 > MAX_PIXELS {
+        return Err(invalid_data("checkpoint of another image size"));
+    }
+    let aov_count = read_u32(&mut reader)?;
+    if aov_count as usize > Aov::ALL.len() {
+        return Err(invalid_data("too many passes"));
+    }
+    let mut aovs = Vec::new();
+    for _ in 0..aov_count {
+        let index = read_u32(&mut reader)? as usize;
+        aovs.push(*Aov::ALL.get(index).ok_or(invalid_data("unknown pass"))?);
+    }
+    let halves = read_u32(&mut reader)? != 0;
+
+    let mut accumulator = Accumulator::new(&aovs, width, height, halves);
+    read_buffer(&mut reader, &mut accumulator.film.sum)?;
+    for weight in accumulator.film.weights.iter_mut() {
+        *weight = read_f32(&mut reader)?;
+    }
+    for i in 0..accumulator.sample_counts.len() {
+        accumulator.sample_counts[i] = read_u32(&mut reader)?;
+        accumulator.hit_counts[i] = read_u32(&mut reader)?;
+        accumulator.statistics[i] = PixelStatistics {
+            count: read_u32(&mut reader)?,
+            mean: read_f32(&mut reader)?,
+            m2: read_f32(&mut reader)?,
+        };
+    }
+    for (_, buffer) in accumulator.aovs.iter_mut() {
+        read_buffer(&mut reader, buffer)?;
+    }
+    if let Some((even, odd)) = &mut accumulator.halves {
+        read_buffer(&mut reader, even)?;
+        read_buffer(&mut reader, odd)?;
+    }
+    Ok(accumulator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderers::{aov::PathSample, filter::Filter};
+
+    #[test]
+    fn should_restore_saved_accumulator() {
+        let mut accumulator = Accumulator::new(&[Aov::Albedo, Aov::SampleCount], 5, 4, true);
+        let filter = Filter::Tent { radius: 1.0 };
+        for (i, (x, y)) in [(0, 0), (2, 1), (2, 1), (4, 3)].into_iter().enumerate() {
+            let sample = PathSample::background(Color::from((i as f32, 0.5, 0.25)));
+            accumulator.add(&filter, x, y, (0.1, -0.2), &sample);
+        }
+
+        let path = std::env::temp_dir().join(format!("checkpoint-{}.bin", std::process::id()));
+        let settings = RenderSettings {
+            max_samples: 64,
+            filter: filter.describe(),
+            sampler: "halton seed=3".to_string(),
+            spectral: true,
+        };
+        save_checkpoint(&accumulator, &settings, &path).unwrap();
+        let restored = load_checkpoint(&path, &settings, 5, 4).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(restored.sample_counts, accumulator.sample_counts);
+        assert_eq!(restored.film.weights, accumulator.film.weights);
+        assert_eq!(restored.statistics[7].m2, accumulator.statistics[7].m2);
+        let (original, resumed) = (accumulator.resolve(), restored.resolve());
+        for (a, b) in original.beauty.pixels.iter().zip(&resumed.beauty.pixels) {
+            assert_eq!((a.x, a.y, a.z), (b.x, b.y, b.z));
+        }
+        let (a, b) = (original.halves.unwrap().1, resumed.halves.unwrap().1);
+        assert_eq!(a.get(2, 1).x, b.get(2, 1).x);
+    }
+
+    #[test]
+    fn should_reject_checkpoints_of_other_renders() {
+        let accumulator = Accumulator::new(&[], 5, 4, false);
+        let settings = RenderSettings {
+            max_samples: 64,
+            filter: Filter::default().describe(),
+            sampler: "halton seed=3".to_string(),
+            spectral: false,
+        };
+        let path =
+            std::env::temp_dir().join(format!("checkpoint-other-{}.bin", std::process::id()));
+        save_checkpoint(&accumulator, &settings, &path).unwrap();
+
+        let other_sampler = RenderSettings {
+            max_samples: 64,
+            filter: Filter::default().describe(),
+            sampler: "sobol seed=3".to_string(),
+            spectral: false,
+        };
+        let error = load_checkpoint(&path, &other_sampler, 5, 4).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        let error = load_checkpoint(&path, &settings, 4, 5).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        // corrupt length of the filter description
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[12..16].copy_from_slice(&u32::MAX.to_le_bytes());
+        fs::write(&path, bytes).unwrap();
+        let error = load_checkpoint(&path, &settings, 5, 4).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        fs::remove_file(&path).unwrap();
+    }
+}
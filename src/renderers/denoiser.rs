@@ -0,0 +1,320 @@
+use std::thread;
+
+use crate::{
+    color::Color,
+    vectors::{ops::MatrixDot, vector3::Vector3},
+};
+
+use super::{
+    aov::{Aov, RenderPasses},
+    framebuffer::FrameBuffer,
+};
+
+/// Lower bound of the albedo dividing the color, for black surfaces.
+const ALBEDO_EPSILON: f32 = 0.01;
+
+/// Feature guided denoiser running on the CPU.
+///
+/// Non-local means on the color, normalized by the per-pixel variance
+/// estimated from two half sample buffers, joined with a bilateral filter
+/// on the normal and albedo passes to keep edges and textures. Filters the
+/// illumination (color divided by albedo) and multiplies the albedo back.
+pub struct Denoiser {
+    /// Half width of the filter window in pixels.
+    pub radius: u32,
+    /// Half width of the patches compared by non-local means.
+    pub patch_radius: u32,
+    /// Strength of the color filter (k in non-local means). Larger values
+    /// blur more.
+    pub strength: f32,
+    /// Standard deviation of the normal difference.
+    pub sigma_normal: f32,
+    /// Standard deviation of the albedo difference.
+    pub sigma_albedo: f32,
+}
+
+impl Default for Denoiser {
+    fn default() -> Self {
+        Denoiser {
+            radius: 6,
+            patch_radius: 1,
+            strength: 0.5,
+            sigma_normal: 0.3,
+            sigma_albedo: 0.1,
+        }
+    }
+}
+
+/// Buffers of a render the denoiser needs.
+struct Features<'a> {
+    illumination: FrameBuffer,
+    variance: FrameBuffer,
+    normal: Option<&'a FrameBuffer>,
+    albedo: Option<&'a FrameBuffer>,
+}
+
+fn demodulate(color: Color, albedo: Option<Color>) -> Color {
+    let Some(albedo) = albedo else {
+        return color;
+    };
+    let channel = |c: f32, a: f32| c / a.max(ALBEDO_EPSILON);
+    Color::from((
+        channel(color.x, albedo.x),
+        channel(color.y, albedo.y),
+        channel(color.z, albedo.z),
+    ))
+}
+
+fn remodulate(color: Color, albedo: Option<Color>) -> Color {
+    let Some(albedo) = albedo else {
+        return color;
+    };
+    let channel = |c: f32, a: f32| c * a.max(ALBEDO_EPSILON);
+    Color::from((
+        channel(color.x, albedo.x),
+        channel(color.y, albedo.y),
+        channel(color.z, albedo.z),
+    ))
+}
+
+/// Mean over the pixels within `radius`, clamped at the borders.
+fn box_filter(buffer: &FrameBuffer, radius: u32) -> FrameBuffer {
+    let mut filtered = FrameBuffer::new(buffer.width, buffer.height);
+    for y in 0..buffer.height {
+        for x in 0..buffer.width {
+            let mut sum = Color::zero();
+            let mut count = 0;
+            for qy in y.saturating_sub(radius)..(y + radius + 1).min(buffer.height) {
+                for qx in x.saturating_sub(radius)..(x + radius + 1).min(buffer.width) {
+                    sum += buffer.get(qx, qy);
+                    count += 1;
+                }
+            }
+            filtered.set(x, y, sum / count as f32);
+        }
+    }
+    filtered
+}
+
+impl Denoiser {
+    /// Denoise the beauty image of the passes. Uses the `Normal` and
+    /// `Albedo` passes when present. Without half buffers the variance is
+    /// unknown and only identical neighborhoods are blended.
+    pub fn denoise(&self, passes: &RenderPasses) -> FrameBuffer {
+        let albedo = passes.get(Aov::Albedo);
+        let albedo_at = |x: u32, y: u32| albedo.map(|albedo| albedo.get(x, y));
+
+        let mut illumination = FrameBuffer::new(passes.beauty.width, passes.beauty.height);
+        for y in 0..illumination.height {
+            for x in 0..illumination.width {
+                illumination.set(x, y, demodulate(passes.beauty.get(x, y), albedo_at(x, y)));
+            }
+        }
+
+        // The halves are independent estimates, so half their difference
+        // estimates the standard deviation of the full estimate.
+        let mut variance = FrameBuffer::new(passes.beauty.width, passes.beauty.height);
+        if let Some((a, b)) = &passes.halves {
+            for y in 0..variance.height {
+                for x in 0..variance.width {
+                    let difference = demodulate(a.get(x, y), albedo_at(x, y))
+                        - demodulate(b.get(x, y), albedo_at(x, y));
+                    variance.set(x, y, difference * difference / 4.0);
+                }
+            }
+            variance = box_filter(&variance, 2);
+        }
+
+        let features = Features {
+            illumination,
+            variance,
+            normal: passes.get(Aov::Normal),
+            albedo,
+        };
+
+        let width = passes.beauty.width;
+        let height = passes.beauty.height;
+        let mut denoised = FrameBuffer::new(width, height);
+        let workers = thread::available_parallelism().map_or(1, |n| n.get());
+        let rows_per_worker = (height as usize).div_ceil(workers).max(1);
+        thread::scope(|scope| {
+            for (chunk, pixels) in denoised
+                .pixels
+                .chunks_mut(rows_per_worker * width as usize)
+                .enumerate()
+            {
+                let features = &features;
+                scope.spawn(move || {
+                    for (i, pixel) in pixels.iter_mut().enumerate() {
+                        let x = (i % width as usize) as u32;
+                        let y = (chunk * rows_per_worker + i / width as usize) as u32;
+                        let filtered = self.filter_pixel(features, x, y);
+                        *pixel = remodulate(filtered, features.albedo.map(|a| a.get(x, y)));
+                    }
+                });
+            }
+        });
+        denoised
+    }
+
+    /// Variance normalized squared color distance of the patches around p
+    /// and q (Rousselle et al., "Robust denoising using feature and color
+    /// information").
+    fn patch_distance(&self, features: &Features, p: (u32, u32), q: (u32, u32)) -> f32 {
+        let Features {
+            illumination,
+            variance,
+            ..
+        } = features;
+        let radius = self.patch_radius as i64;
+        let (width, height) = (illumination.width as i64, illumination.height as i64);
+        let mut distance = 0.0;
+        let mut count = 0;
+        for dy in -radius..=radius {
+            for dx in -radius..=radius {
+                let (px, py) = (p.0 as i64 + dx, p.1 as i64 + dy);
+                let (qx, qy) = (q.0 as i64 + dx, q.1 as i64 + dy);
+                if px < 0 || py < 0 || qx < 0 || qy < 0 {
+                    continue;
+                }
+                if px >= width || py >= height || qx >= width || qy >= height {
+                    continue;
+                }
+                let (px, py, qx, qy) = (px as u32, py as u32, qx as u32, qy as u32);
+                let cp = illumination.get(px, py);
+                let cq = illumination.get(qx, qy);
+                let vp = variance.get(px, py);
+                let vq = variance.get(qx, qy);
+                for channel in 0..3 {
+                    let numerator = (cp[channel] - cq[channel]).powi(2)
+                        - (vp[channel] + vp[channel].min(vq[channel]));
+                    let denominator = 1.0e-10 + self.strength.powi(2) * (vp[channel] + vq[channel]);
+                    distance += numerator / denominator;
+                }
+                count += 3;
+            }
+        }
+        if count == 0 {
+            0.0
+        } else {
+            (distance / count as f32).max(0.0)
+        }
+    }
+
+    fn filter_pixel(&self, features: &Features, x: u32, y: u32) -> Color {
+        let illumination = &features.illumination;
+        let radius = self.radius;
+        let mut sum = Color::zero();
+        let mut weight_sum = 0.0;
+        for qy in y.saturating_sub(radius)..(y + radius + 1).min(illumination.height) {
+            for qx in x.saturating_sub(radius)..(x + radius + 1).min(illumination.width) {
+                let mut exponent = self.patch_distance(features, (x, y), (qx, qy));
+                if let Some(normal) = features.normal {
+                    let difference: Vector3 = normal.get(x, y) - normal.get(qx, qy);
+                    exponent += difference.dot(&difference) / (2.0 * self.sigma_normal.powi(2));
+                }
+                if let Some(albedo) = features.albedo {
+                    let difference: Vector3 = albedo.get(x, y) - albedo.get(qx, qy);
+                    exponent += difference.dot(&difference) / (2.0 * self.sigma_albedo.powi(2));
+                }
+                let weight = (-exponent).exp();
+                sum += illumination.get(qx, qy) * weight;
+                weight_sum += weight;
+            }
+        }
+        // The center pixel always has weight 1.
+        sum / weight_sum
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::samplers::sampler::{hash, to_unit_float};
+
+    const WIDTH: u32 = 48;
+    const HEIGHT: u32 = 32;
+
+    /// Two surfaces meeting at the middle of the image, under smoothly
+    /// varying light.
+    fn albedo(x: u32) -> Color {
+        if x < WIDTH / 2 {
+            Color::from((0.7, 0.2, 0.1))
+        } else {
+            Color::from((0.5, 0.5, 0.5))
+        }
+    }
+
+    fn normal(x: u32) -> Color {
+        if x < WIDTH / 2 {
+            Color::from((0.0, 1.0, 0.0))
+        } else {
+            Color::from((0.0, 0.0, 1.0))
+        }
+    }
+
+    fn clean(x: u32, y: u32) -> Color {
+        albedo(x) * (0.5 + 0.5 * y as f32 / HEIGHT as f32)
+    }
+
+    /// Half buffer of the clean image plus zero mean noise.
+    fn noisy_half(seed: u32) -> FrameBuffer {
+        let mut buffer = FrameBuffer::new(WIDTH, HEIGHT);
+        for y in 0..HEIGHT {
+            for x in 0..WIDTH {
+                let noise = |channel: u32| to_unit_float(hash(&[seed, x, y, channel])) - 0.5;
+                let noise = Color::from((noise(0), noise(1), noise(2))) * 0.4;
+                buffer.set(x, y, clean(x, y) + noise);
+            }
+        }
+        buffer
+    }
+
+    fn feature(f: impl Fn(u32) -> Color) -> FrameBuffer {
+        let mut buffer = FrameBuffer::new(WIDTH, HEIGHT);
+        for y in 0..HEIGHT {
+            for x in 0..WIDTH {
+                buffer.set(x, y, f(x));
+            }
+        }
+        buffer
+    }
+
+    fn mean_squared_error(a: &FrameBuffer, f: impl Fn(u32, u32) -> Color) -> f32 {
+        let mut sum = 0.0;
+        for y in 0..a.height {
+            for x in 0..a.width {
+                let difference = a.get(x, y) - f(x, y);
+                sum += difference.dot(&difference);
+            }
+        }
+        sum / a.pixels.len() as f32
+    }
+
+    #[test]
+    fn should_approach_the_clean_image() {
+        let (even, odd) = (noisy_half(1), noisy_half(2));
+        let mut beauty = FrameBuffer::new(WIDTH, HEIGHT);
+        for (i, pixel) in beauty.pixels.iter_mut().enumerate() {
+            *pixel = (even.pixels[i] + odd.pixels[i]) / 2.0;
+        }
+        let passes = RenderPasses {
+            beauty,
+            aovs: vec![
+                (Aov::Albedo, feature(albedo)),
+                (Aov::Normal, feature(normal)),
+            ],
+            halves: Some((even, odd)),
+        };
+        let denoised = Denoiser::default().denoise(&passes);
+
+        let noisy_error = mean_squared_error(&passes.beauty, clean);
+        let denoised_error = mean_squared_error(&denoised, clean);
+        assert!(
+            denoised_error < 0.5 * noisy_error,
+            "{} {}",
+            denoised_error,
+            noisy_error
+        );
+    }
+}
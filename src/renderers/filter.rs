@@ -0,0 +1,157 @@
+use std::f32::consts::PI;
+
+/// Pixel reconstruction filter. Each sample is splatted into the pixels
+/// within `radius` (in pixels) of it, weighted by the filter, and every
+/// pixel is normalized by the sum of the weights it received.
+pub enum Filter {
+    Box {
+        radius: f32,
+    },
+    Tent {
+        radius: f32,
+    },
+    /// Gaussian shifted to reach zero at the radius.
+    Gaussian {
+        radius: f32,
+        sigma: f32,
+    },
+    /// Cubic of Mitchell and Netravali. b = c = 1/3 is their recommendation.
+    MitchellNetravali {
+        radius: f32,
+        b: f32,
+        c: f32,
+    },
+    /// Sinc windowed by a wider sinc reaching zero at the radius.
+    Lanczos {
+        radius: f32,
+    },
+}
+
+impl Default for Filter {
+    /// Average of the samples inside of each pixel.
+    fn default() -> Self {
+        Filter::Box { radius: 0.5 }
+    }
+}
+
+fn gaussian(x: f32, sigma: f32) -> f32 {
+    (-x * x / (2.0 * sigma * sigma)).exp()
+}
+
+fn mitchell_netravali(x: f32, b: f32, c: f32) -> f32 {
+    let x = x.abs();
+    if x < 1.0 {
+        ((12.0 - 9.0 * b - 6.0 * c) * x.powi(3)
+            + (-18.0 + 12.0 * b + 6.0 * c) * x.powi(2)
+            + (6.0 - 2.0 * b))
+            / 6.0
+    } else if x < 2.0 {
+        ((-b - 6.0 * c) * x.powi(3)
+            + (6.0 * b + 30.0 * c) * x.powi(2)
+            + (-12.0 * b - 48.0 * c) * x
+            + (8.0 * b + 24.0 * c))
+            / 6.0
+    } else {
+        0.0
+    }
+}
+
+fn sinc(x: f32) -> f32 {
+    if x.abs() < 1.0e-5 {
+        1.0
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+impl Filter {
+    pub fn radius(&self) -> f32 {
+        match self {
+            Filter::Box { radius }
+            | Filter::Tent { radius }
+            | Filter::Gaussian { radius, .. }
+            | Filter::MitchellNetravali { radius, .. }
+            | Filter::Lanczos { radius } => *radius,
+        }
+    }
+
+    /// Kind and parameters of the filter, e.g. to check that a checkpoint
+    /// is resumed with the same filter.
+    pub fn describe(&self) -> String {
+        match self {
+            Filter::Box { radius } => format!("box radius={}", radius),
+            Filter::Tent { radius } => format!("tent radius={}", radius),
+            Filter::Gaussian { radius, sigma } => {
+                format!("gaussian radius={} sigma={}", radius, sigma)
+            }
+            Filter::MitchellNetravali { radius, b, c } => {
+                format!("mitchell_netravali radius={} b={} c={}", radius, b, c)
+            }
+            Filter::Lanczos { radius } => format!("lanczos radius={}", radius),
+        }
+    }
+
+    fn evaluate_1d(&self, x: f32) -> f32 {
+        let radius = self.radius();
+        if x.abs() > radius {
+            return 0.0;
+        }
+        match self {
+            Filter::Box { .. } => 1.0,
+            Filter::Tent { .. } => radius - x.abs(),
+            Filter::Gaussian { sigma, .. } => {
+                (gaussian(x, *sigma) - gaussian(radius, *sigma)).max(0.0)
+            }
+            // the cubic spans [-2, 2]
+            Filter::MitchellNetravali { b, c, .. } => mitchell_netravali(2.0 * x / radius, *b, *c),
+            Filter::Lanczos { .. } => sinc(x) * sinc(x / radius),
+        }
+    }
+
+    /// Weight of a sample at the offset (in pixels) from a pixel center.
+    /// May be negative for filters with negative lobes.
+    pub fn evaluate(&self, offset: (f32, f32)) -> f32 {
+        self.evaluate_1d(offset.0) * self.evaluate_1d(offset.1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{color::Color, renderers::framebuffer::SplatBuffer};
+
+    #[test]
+    fn should_keep_constant_image_constant() {
+        let filters = [
+            Filter::default(),
+            Filter::Tent { radius: 1.5 },
+            Filter::Gaussian {
+                radius: 1.5,
+                sigma: 0.5,
+            },
+            Filter::MitchellNetravali {
+                radius: 2.0,
+                b: 1.0 / 3.0,
+                c: 1.0 / 3.0,
+            },
+            Filter::Lanczos { radius: 3.0 },
+        ];
+        let color = Color::from((0.2, 0.4, 0.8));
+        for filter in filters {
+            let mut buffer = SplatBuffer::new(8, 6);
+            for y in 0..6 {
+                for x in 0..8 {
+                    for i in 0..16 {
+                        let offset = ((i % 4) as f32 / 4.0 - 0.375, (i / 4) as f32 / 4.0 - 0.375);
+                        buffer.splat(&filter, x, y, offset, color);
+                    }
+                }
+            }
+            let image = buffer.resolve();
+            for pixel in image.pixels {
+                let difference = pixel - color;
+                assert!(difference.norm() < 1.0e-4, "{}", filter.radius());
+            }
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use std::path::Path;
+
+use image::{ImageResult, Rgb, Rgb32FImage, RgbImage};
+
+use crate::color::{get_rgb, Color};
+
+use super::filter::Filter;
+
+/// Linear float RGB image accumulating rendered radiance.
+pub struct FrameBuffer {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<Color>,
+}
+
+impl FrameBuffer {
+    pub fn new(width: u32, height: u32) -> Self {
+        FrameBuffer {
+            width,
+            height,
+            pixels: vec![Color::zero(); (width * height) as usize],
+        }
+    }
+
+    pub fn index(&self, x: u32, y: u32) -> usize {
+        (y * self.width + x) as usize
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> Color {
+        self.pixels[self.index(x, y)]
+    }
+
+    pub fn set(&mut self, x: u32, y: u32, color: Color) {
+        let index = self.index(x, y);
+        self.pixels[index] = color;
+    }
+
+    /// New buffer with `f` applied to every pixel.
+    pub fn map(&self, f: impl Fn(Color) -> Color) -> FrameBuffer {
+        FrameBuffer {
+            width: self.width,
+            height: self.height,
+            pixels: self.pixels.iter().map(|color| f(*color)).collect(),
+        }
+    }
+
+    /// Gamma corrected 8-bit image.
+    pub fn to_rgb_image(&self) -> RgbImage {
+        RgbImage::from_fn(self.width, self.height, |x, y| get_rgb(&self.get(x, y)))
+    }
+
+    pub fn to_rgb32f_image(&self) -> Rgb32FImage {
+        Rgb32FImage::from_fn(self.width, self.height, |x, y| {
+            let color = self.get(x, y);
+            Rgb([color.x, color.y, color.z])
+        })
+    }
+
+    /// Save as linear OpenEXR if the path ends with `.exr`, otherwise as a
+    /// gamma corrected 8-bit image.
+    pub fn save(&self, path: &Path) -> ImageResult<()> {
+        if is_exr(path) {
+            self.to_rgb32f_image().save(path)
+        } else {
+            self.to_rgb_image().save(path)
+        }
+    }
+}
+
+pub fn is_exr(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("exr"))
+}
+
+/// Float image accumulating samples splatted through a reconstruction
+/// filter, along with the sum of the filter weights per pixel.
+pub struct SplatBuffer {
+    pub sum: FrameBuffer,
+    pub weights: Vec<f32>,
+}
+
+impl SplatBuffer {
+    pub fn new(width: u32, height: u32) -> Self {
+        SplatBuffer {
+            sum: FrameBuffer::new(width, height),
+            weights: vec![0.0; (width * height) as usize],
+        }
+    }
+
+    /// Add a sample taken at `offset` (in pixels, within [-0.5, 0.5)) from
+    /// the center of pixel (x, y) to all pixels in the support of the
+    /// filter.
+    pub fn splat(&mut self, filter: &Filter, x: u32, y: u32, offset: (f32, f32), color: Color) {
+        let radius = filter.radius();
+        let (fx, fy) = (x as f32 + offset.0, y as f32 + offset.1);
+        let x_min = (fx - radius).ceil().max(0.0) as u32;
+        let y_min = (fy - radius).ceil().max(0.0) as u32;
+        let x_max = ((fx + radius).floor() as i64).min(self.sum.width as i64 - 1);
+        let y_max = ((fy + radius).floor() as i64).min(self.sum.height as i64 - 1);
+        for py in y_min as i64..=y_max {
+            for px in x_min as i64..=x_max {
+                let (px, py) = (px as u32, py as u32);
+                let weight = filter.evaluate((px as f32 - fx, py as f32 - fy));
+                if weight == 0.0 {
+                    continue;
+                }
+                let index = self.sum.index(px, py);
+                self.sum.pixels[index] += color * weight;
+                self.weights[index] += weight;
+            }
+        }
+    }
+
+    /// Image normalized by the filter weights.
+    pub fn resolve(&self) -> FrameBuffer {
+        let mut image = FrameBuffer::new(self.sum.width, self.sum.height);
+        for (i, pixel) in image.pixels.iter_mut().enumerate() {
+            let weight = self.weights[i];
+            if weight.abs() > 1.0e-6 {
+                *pixel = self.sum.pixels[i] / weight;
+            }
+        }
+        image
+    }
+}
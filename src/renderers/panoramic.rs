@@ -0,0 +1,278 @@
+use std::f32::consts::PI;
+
+use crate::{optical::ray::Ray, vectors::vector3::Vector3};
+
+use super::{
+    camera::{ImageSize, Rect},
+    camera_model::{image_uv, CameraModel, CameraView},
+};
+
+/// Direction at the given longitude (0 ahead, positive to the right) and
+/// latitude (positive up), in camera space.
+pub fn spherical_direction(longitude: f32, latitude: f32) -> Vector3 {
+    Vector3::from((
+        latitude.cos() * longitude.sin(),
+        latitude.sin(),
+        -latitude.cos() * longitude.cos(),
+    ))
+}
+
+/// Full sphere in latitude-longitude layout, 360° wide and 180° high. Use
+/// an aspect ratio of 2.
+pub struct EquirectangularCamera {
+    pub view: CameraView,
+    pub image_size: ImageSize,
+}
+
+impl CameraModel for EquirectangularCamera {
+    fn image_rect(&self) -> Rect<u32> {
+        self.image_size.rect()
+    }
+
+    fn get_ray(
+        &self,
+        x: u32,
+        y: u32,
+        pixel_sample: (f32, f32),
+        _lens_sample: (f32, f32),
+    ) -> Option<Ray> {
+        let (u, v) = image_uv(self.image_rect(), x, y, pixel_sample);
+        let direction = spherical_direction((u - 0.5) * 2.0 * PI, (0.5 - v) * PI);
+        Some(self.view.ray(direction))
+    }
+}
+
+/// Mapping of the angle from the view direction to the distance from the
+/// center of a fisheye image.
+pub enum FisheyeMapping {
+    /// Distance proportional to the angle.
+    Equidistant,
+    /// Preserves areas, `r ~ sin(angle / 2)`.
+    Equisolid,
+}
+
+/// Fisheye lens filling the circle inscribed into the image. Pixels outside
+/// of the circle stay black.
+pub struct FisheyeCamera {
+    pub view: CameraView,
+    pub image_size: ImageSize,
+    pub mapping: FisheyeMapping,
+    /// Field of view across the circle, up to 360°.
+    pub fov_deg: f32,
+}
+
+impl CameraModel for FisheyeCamera {
+    fn image_rect(&self) -> Rect<u32> {
+        self.image_size.rect()
+    }
+
+    fn get_ray(
+        &self,
+        x: u32,
+        y: u32,
+        pixel_sample: (f32, f32),
+        _lens_sample: (f32, f32),
+    ) -> Option<Ray> {
+        let rect = self.image_rect();
+        let radius = rect.width.min(rect.height) as f32 / 2.0;
+        let px = (x as f32 + pixel_sample.0 - rect.width as f32 / 2.0) / radius;
+        let py = (rect.height as f32 / 2.0 - y as f32 - pixel_sample.1) / radius;
+        let r = (px * px + py * py).sqrt();
+        if r > 1.0 {
+            return None;
+        }
+
+        let max_angle = self.fov_deg.to_radians().min(2.0 * PI) / 2.0;
+        let angle = match self.mapping {
+            FisheyeMapping::Equidistant => r * max_angle,
+            FisheyeMapping::Equisolid => 2.0 * (r * (max_angle / 2.0).sin()).asin(),
+        };
+        let (sin, cos) = angle.sin_cos();
+        let (dx, dy) = if r > 0.0 {
+            (px / r, py / r)
+        } else {
+            (0.0, 0.0)
+        };
+        let direction = Vector3::from((dx * sin, dy * sin, -cos));
+        Some(self.view.ray(direction))
+    }
+}
+
+/// Six 90° faces side by side, in the order +X, -X, +Y, -Y, +Z, -Z of the
+/// camera space with the face orientations of OpenGL cube maps. The view
+/// direction is the center of the -Z face.
+pub struct CubemapCamera {
+    pub view: CameraView,
+    /// Edge length of a face in pixels.
+    pub face_size: u32,
+}
+
+impl CameraModel for CubemapCamera {
+    fn image_rect(&self) -> Rect<u32> {
+        Rect {
+            width: 6 * self.face_size.max(1),
+            height: self.face_size.max(1),
+        }
+    }
+
+    fn get_ray(
+        &self,
+        x: u32,
+        y: u32,
+        pixel_sample: (f32, f32),
+        _lens_sample: (f32, f32),
+    ) -> Option<Ray> {
+        let face_size = self.face_size.max(1);
+        let face = x / face_size;
+        let s = 2.0 * ((x % face_size) as f32 + pixel_sample.0) / face_size as f32 - 1.0;
+        let t = 2.0 * (y as f32 + pixel_sample.1) / face_size as f32 - 1.0;
+        let direction = match face {
+            0 => Vector3::from((1.0, -t, -s)),
+            1 => Vector3::from((-1.0, -t, s)),
+            2 => Vector3::from((s, 1.0, t)),
+            3 => Vector3::from((s, -1.0, -t)),
+            4 => Vector3::from((s, -t, 1.0)),
+            _ => Vector3::from((-s, -t, -1.0)),
+        };
+        Some(self.view.ray(direction))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        renderers::camera::CameraGeometryParam,
+        vectors::{ops::MatrixDot, vector3::Point3},
+    };
+
+    fn view() -> CameraView {
+        CameraView::from(CameraGeometryParam {
+            center: Point3::from((1.0, 2.0, 3.0)),
+            lookat: Point3::from((1.0, 2.0, 0.0)),
+            up: Vector3::from((0.0, 1.0, 0.0)),
+        })
+    }
+
+    fn assert_direction(camera: &dyn CameraModel, (x, y): (u32, u32), expected: Vector3) {
+        let ray = camera.get_ray(x, y, (0.5, 0.5), (0.5, 0.5)).unwrap();
+        assert!(ray.direction.to_unit().dot(&expected) > 0.99);
+    }
+
+    #[test]
+    fn should_look_ahead_at_the_image_center() {
+        let ahead = Vector3::from((0.0, 0.0, -1.0));
+        let up = Vector3::from((0.0, 1.0, 0.0));
+        let right = Vector3::from((1.0, 0.0, 0.0));
+        let equirectangular = EquirectangularCamera {
+            view: view(),
+            image_size: ImageSize {
+                aspect_ratio: 2.0,
+                width: 64,
+            },
+        };
+        assert_direction(&equirectangular, (31, 15), ahead);
+        assert_direction(&equirectangular, (47, 15), right);
+        assert_direction(&equirectangular, (31, 0), up);
+
+        let fisheye = FisheyeCamera {
+            view: view(),
+            image_size: ImageSize {
+                aspect_ratio: 1.0,
+                width: 33,
+            },
+            mapping: FisheyeMapping::Equisolid,
+            fov_deg: 180.0,
+        };
+        assert_direction(&fisheye, (16, 16), ahead);
+        assert_direction(&fisheye, (32, 16), right);
+        assert!(fisheye.get_ray(0, 0, (0.5, 0.5), (0.5, 0.5)).is_none());
+
+        let cubemap = CubemapCamera {
+            view: view(),
+            face_size: 9,
+        };
+        assert_direction(&cubemap, (5 * 9 + 4, 4), ahead);
+        assert_direction(&cubemap, (4, 4), right);
+        assert_direction(&cubemap, (2 * 9 + 4, 4), up);
+    }
+
+    fn assert_sample_direction(
+        camera: &dyn CameraModel,
+        (x, y): (u32, u32),
+        pixel_sample: (f32, f32),
+        expected: Vector3,
+    ) {
+        let ray = camera.get_ray(x, y, pixel_sample, (0.5, 0.5)).unwrap();
+        let expected = expected.to_unit();
+        assert!((ray.direction.to_unit() - expected).norm() < 1e-4);
+    }
+
+    #[test]
+    fn should_map_fisheye_radius_to_angle() {
+        let (sin, cos) = std::f32::consts::FRAC_PI_4.sin_cos();
+        // half way to the edge of a 33 pixel circle
+        let right = ((24, 16), (0.75, 0.5));
+        let up = ((16, 8), (0.5, 0.25));
+
+        let equidistant = FisheyeCamera {
+            view: view(),
+            image_size: ImageSize {
+                aspect_ratio: 1.0,
+                width: 33,
+            },
+            mapping: FisheyeMapping::Equidistant,
+            fov_deg: 180.0,
+        };
+        assert_sample_direction(
+            &equidistant,
+            right.0,
+            right.1,
+            Vector3::from((sin, 0.0, -cos)),
+        );
+        assert_sample_direction(&equidistant, up.0, up.1, Vector3::from((0.0, sin, -cos)));
+
+        let equisolid = FisheyeCamera {
+            mapping: FisheyeMapping::Equisolid,
+            ..equidistant
+        };
+        let angle = 2.0 * (0.5 * sin).asin();
+        let (sin, cos) = angle.sin_cos();
+        assert_sample_direction(
+            &equisolid,
+            right.0,
+            right.1,
+            Vector3::from((sin, 0.0, -cos)),
+        );
+        assert_sample_direction(&equisolid, up.0, up.1, Vector3::from((0.0, sin, -cos)));
+
+        // the edge of a full sphere fisheye looks backwards
+        let full = FisheyeCamera {
+            mapping: FisheyeMapping::Equidistant,
+            fov_deg: 360.0,
+            ..equisolid
+        };
+        assert_sample_direction(&full, (32, 16), (1.0, 0.5), Vector3::from((0.0, 0.0, 1.0)));
+    }
+
+    #[test]
+    fn should_orient_cubemap_faces() {
+        let cubemap = CubemapCamera {
+            view: view(),
+            face_size: 9,
+        };
+        let faces = [
+            ((1.0, 0.0, 0.0), (1.0, 1.0, -1.0)),
+            ((-1.0, 0.0, 0.0), (-1.0, 1.0, 1.0)),
+            ((0.0, 1.0, 0.0), (1.0, 1.0, -1.0)),
+            ((0.0, -1.0, 0.0), (1.0, -1.0, 1.0)),
+            ((0.0, 0.0, 1.0), (1.0, 1.0, 1.0)),
+            ((0.0, 0.0, -1.0), (-1.0, 1.0, -1.0)),
+        ];
+        for (face, (center, top_right)) in faces.into_iter().enumerate() {
+            let x = face as u32 * 9;
+            assert_sample_direction(&cubemap, (x + 4, 4), (0.5, 0.5), Vector3::from(center));
+            assert_sample_direction(&cubemap, (x + 8, 0), (1.0, 0.0), Vector3::from(top_right));
+        }
+    }
+}
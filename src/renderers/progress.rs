@@ -0,0 +1,147 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use log::debug;
+
+/// State of a running render, reported to a `ProgressObserver` after every
+/// finished tile.
+pub struct Progress {
+    /// Pass being rendered, counting from 1.
+    pub pass: u32,
+    pub passes: u32,
+    /// Tiles finished over all passes.
+    pub tiles_done: u32,
+    pub tiles_total: u32,
+    /// Camera samples traced in this render.
+    pub samples: u64,
+    /// Rays intersected with the scene in this render.
+    pub rays: u64,
+    pub elapsed: Duration,
+    /// Share of the sample budget of the render done, in [0, 1].
+    pub fraction: f32,
+}
+
+impl Progress {
+    /// Estimated time left, once some work is done.
+    pub fn eta(&self) -> Option<Duration> {
+        if self.fraction <= 0.0 {
+            return None;
+        }
+        let remaining = (1.0 - self.fraction).max(0.0) / self.fraction;
+        Some(self.elapsed.mul_f32(remaining))
+    }
+
+    pub fn rays_per_second(&self) -> f32 {
+        self.rays as f32 / self.elapsed.as_secs_f32().max(f32::EPSILON)
+    }
+
+    pub fn samples_per_second(&self) -> f32 {
+        self.samples as f32 / self.elapsed.as_secs_f32().max(f32::EPSILON)
+    }
+}
+
+/// Receives the progress of a render, e.g. to show it in a GUI.
+pub trait ProgressObserver {
+    fn progress(&self, progress: &Progress);
+
+    /// Called once when the render finished or was cancelled.
+    fn finished(&self, _progress: &Progress) {}
+}
+
+impl<F: Fn(&Progress)> ProgressObserver for F {
+    fn progress(&self, progress: &Progress) {
+        self(progress)
+    }
+}
+
+/// Logs the progress at debug level.
+pub struct LogProgress;
+
+impl ProgressObserver for LogProgress {
+    fn progress(&self, progress: &Progress) {
+        let eta = match progress.eta() {
+            Some(eta) => format!("{:.0}s", eta.as_secs_f32()),
+            None => "-".to_string(),
+        };
+        debug!(
+            "\rPass {}/{}, tiles {}/{}, {:.2} Mrays/s, ETA {}   ",
+            progress.pass,
+            progress.passes,
+            progress.tiles_done,
+            progress.tiles_total,
+            progress.rays_per_second() / 1e6,
+            eta
+        );
+    }
+
+    fn finished(&self, progress: &Progress) {
+        debug!(
+            "\rDone in {:.1}s, {} samples, {:.2} Mrays/s.          \n",
+            progress.elapsed.as_secs_f32(),
+            progress.samples,
+            progress.rays_per_second() / 1e6
+        );
+    }
+}
+
+/// Shared flag to abort a render from another thread. The render stops
+/// after the pixel in flight and returns the samples taken until then.
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        CancellationToken {
+            cancelled: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clone for CancellationToken {
+    fn clone(&self) -> Self {
+        CancellationToken {
+            cancelled: Arc::clone(&self.cancelled),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_estimate_remaining_time() {
+        let progress = Progress {
+            pass: 1,
+            passes: 1,
+            tiles_done: 1,
+            tiles_total: 4,
+            samples: 100,
+            rays: 400,
+            elapsed: Duration::from_secs(10),
+            fraction: 0.25,
+        };
+        assert_eq!(progress.eta(), Some(Duration::from_secs(30)));
+        assert_eq!(progress.rays_per_second(), 40.0);
+    }
+}
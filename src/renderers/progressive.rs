@@ -0,0 +1,77 @@
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// Render in passes of increasing sample count, writing the image so far
+/// regularly. Setting `stop` ends the render early with the samples taken
+/// until then.
+pub struct Progressive {
+    /// Samples per pixel of the first pass. Every further pass doubles the
+    /// samples of the image until the final sample count.
+    pub initial_samples: u32,
+    /// Write the image after every this many passes (0 for never).
+    pub write_every_passes: u32,
+    /// Write the image at least this often during long passes.
+    pub write_every: Option<Duration>,
+    /// Image the progress is written to, see `RenderPasses::save_images`.
+    pub output: PathBuf,
+    pub stop: Arc<AtomicBool>,
+}
+
+impl Progressive {
+    pub fn new(output: PathBuf) -> Self {
+        Progressive {
+            initial_samples: 1,
+            write_every_passes: 1,
+            write_every: Some(Duration::from_secs(60)),
+            output,
+            stop: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Total samples per pixel after each pass.
+    pub fn pass_targets(&self, max_samples: u32) -> Vec<u32> {
+        let mut targets = Vec::new();
+        let mut target = self.initial_samples.clamp(1, max_samples.max(1));
+        loop {
+            targets.push(target.min(max_samples));
+            if target >= max_samples {
+                break;
+            }
+            target = target.saturating_mul(2);
+        }
+        targets
+    }
+
+    pub fn stopped(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_double_passes_until_the_sample_count() {
+        let progressive = Progressive::new(PathBuf::from("progress.png"));
+        assert_eq!(
+            progressive.pass_targets(100),
+            vec![1, 2, 4, 8, 16, 32, 64, 100]
+        );
+        assert_eq!(progressive.pass_targets(64), vec![1, 2, 4, 8, 16, 32, 64]);
+        assert_eq!(progressive.pass_targets(1), vec![1]);
+
+        let progressive = Progressive {
+            initial_samples: 16,
+            ..Progressive::new(PathBuf::from("progress.png"))
+        };
+        assert_eq!(progressive.pass_targets(40), vec![16, 32, 40]);
+        assert_eq!(progressive.pass_targets(8), vec![8]);
+    }
+}
@@ -2,6 +2,28 @@ use image::RgbImage;
 
 use crate::world::World;
 
+use super::{
+    aov::RenderPasses,
+    progress::{CancellationToken, LogProgress, ProgressObserver},
+};
+
 pub trait Renderer {
-    fn render(&self, world: &'static World) -> RgbImage;
+    fn render(&self, world: &'static World) -> RgbImage {
+        self.render_passes(world).beauty.to_rgb_image()
+    }
+
+    /// Render the beauty image in float along with the configured render
+    /// passes.
+    fn render_passes(&self, world: &'static World) -> RenderPasses {
+        self.render_observed(world, &LogProgress, &CancellationToken::new())
+    }
+
+    /// `render_passes` reporting the progress to `observer`, stopping early
+    /// with the samples so far once `cancel` is cancelled.
+    fn render_observed(
+        &self,
+        world: &'static World,
+        observer: &dyn ProgressObserver,
+        cancel: &CancellationToken,
+    ) -> RenderPasses;
 }
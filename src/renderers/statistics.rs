@@ -0,0 +1,190 @@
+use std::{cell::RefCell, fmt::Write, time::Duration};
+
+/// Counters of a render, collected per thread and merged by the renderer.
+pub struct Statistics {
+    /// Camera rays.
+    pub primary_rays: u64,
+    /// Rays scattered at a surface or inside of a medium.
+    pub secondary_rays: u64,
+    /// Intersection tests by the type of the primitive tested.
+    pub intersection_tests: Vec<(&'static str, u64)>,
+    /// Wall-clock time spent in each phase of the render.
+    pub phases: Vec<(&'static str, Duration)>,
+}
+
+/// Format of the statistics printed at the end of a render.
+pub enum StatisticsReport {
+    Text,
+    Json,
+}
+
+thread_local! {
+    static CURRENT: RefCell<Statistics> = RefCell::new(Statistics::new());
+}
+
+/// Update the counters of the current thread.
+pub fn record(f: impl FnOnce(&mut Statistics)) {
+    CURRENT.with(|statistics| f(&mut statistics.borrow_mut()))
+}
+
+/// Counters of the current thread since the last call, resetting them.
+pub fn take() -> Statistics {
+    CURRENT.with(|statistics| statistics.replace(Statistics::new()))
+}
+
+/// Name of a type without its module path and generic parameters.
+pub fn short_type_name(name: &'static str) -> &'static str {
+    let name = name.split('<').next().unwrap_or(name);
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+impl Default for Statistics {
+    fn default() -> Self {
+        Statistics::new()
+    }
+}
+
+impl Statistics {
+    pub fn new() -> Self {
+        Statistics {
+            primary_rays: 0,
+            secondary_rays: 0,
+            intersection_tests: Vec::new(),
+            phases: Vec::new(),
+        }
+    }
+
+    pub fn rays(&self) -> u64 {
+        self.primary_rays + self.secondary_rays
+    }
+
+    /// Path segments per camera ray.
+    pub fn average_path_depth(&self) -> f32 {
+        (self.primary_rays + self.secondary_rays) as f32 / self.primary_rays.max(1) as f32
+    }
+
+    pub fn count_intersection(&mut self, primitive: &'static str) {
+        self.add_intersections(primitive, 1);
+    }
+
+    fn add_intersections(&mut self, primitive: &'static str, count: u64) {
+        match self
+            .intersection_tests
+            .iter_mut()
+            .find(|(name, _)| *name == primitive)
+        {
+            Some((_, tests)) => *tests += count,
+            None => self.intersection_tests.push((primitive, count)),
+        }
+    }
+
+    pub fn add_phase(&mut self, phase: &'static str, duration: Duration) {
+        match self.phases.iter_mut().find(|(name, _)| *name == phase) {
+            Some((_, total)) => *total += duration,
+            None => self.phases.push((phase, duration)),
+        }
+    }
+
+    pub fn merge(&mut self, other: &Statistics) {
+        self.primary_rays += other.primary_rays;
+        self.secondary_rays += other.secondary_rays;
+        for (primitive, count) in &other.intersection_tests {
+            self.add_intersections(primitive, *count);
+        }
+        for (phase, duration) in &other.phases {
+            self.add_phase(phase, *duration);
+        }
+    }
+
+    fn total_time(&self) -> Duration {
+        self.phases.iter().map(|(_, duration)| *duration).sum()
+    }
+
+    /// Human-readable summary.
+    pub fn report(&self) -> String {
+        let seconds = self.total_time().as_secs_f32().max(f32::EPSILON);
+        let mut report = String::new();
+        writeln!(report, "Render statistics").unwrap();
+        writeln!(
+            report,
+            "  Rays: {} primary, {} secondary, {:.2} Mrays/s",
+            self.primary_rays,
+            self.secondary_rays,
+            self.rays() as f32 / seconds / 1e6
+        )
+        .unwrap();
+        writeln!(
+            report,
+            "  Average path depth: {:.2}",
+            self.average_path_depth()
+        )
+        .unwrap();
+        writeln!(report, "  Intersection tests:").unwrap();
+        for (primitive, count) in &self.intersection_tests {
+            writeln!(report, "    {}: {}", primitive, count).unwrap();
+        }
+        writeln!(report, "  Time:").unwrap();
+        for (phase, duration) in &self.phases {
+            writeln!(report, "    {}: {:.3}s", phase, duration.as_secs_f32()).unwrap();
+        }
+        report
+    }
+
+    /// The counters as a JSON object.
+    pub fn to_json(&self) -> String {
+        let entries = |values: Vec<(&str, String)>| {
+            let values: Vec<String> = values
+                .into_iter()
+                .map(|(name, value)| format!("\"{}\":{}", name, value))
+                .collect();
+            format!("{{{}}}", values.join(","))
+        };
+        let intersection_tests = self
+            .intersection_tests
+            .iter()
+            .map(|(primitive, count)| (*primitive, count.to_string()))
+            .collect();
+        let phases = self
+            .phases
+            .iter()
+            .map(|(phase, duration)| (*phase, duration.as_secs_f64().to_string()))
+            .collect();
+        entries(vec![
+            ("primary_rays", self.primary_rays.to_string()),
+            ("secondary_rays", self.secondary_rays.to_string()),
+            ("average_path_depth", self.average_path_depth().to_string()),
+            ("intersection_tests", entries(intersection_tests)),
+            ("phase_seconds", entries(phases)),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_merge_and_write_json() {
+        let mut total = Statistics::new();
+        for _ in 0..2 {
+            record(|statistics| {
+                statistics.primary_rays += 1;
+                statistics.secondary_rays += 2;
+                statistics.count_intersection("Sphere");
+            });
+            total.merge(&take());
+        }
+        total.add_phase("sampling", Duration::from_millis(500));
+
+        assert_eq!(total.average_path_depth(), 3.0);
+        assert_eq!(
+            total.to_json(),
+            "{\"primary_rays\":2,\"secondary_rays\":4,\"average_path_depth\":3,\
+             \"intersection_tests\":{\"Sphere\":2},\"phase_seconds\":{\"sampling\":0.5}}"
+        );
+        assert_eq!(
+            short_type_name("rust_tutorial::objects::sdf_shape::SdfShape<T>"),
+            "SdfShape"
+        );
+    }
+}
@@ -0,0 +1,187 @@
+use std::f32::consts::PI;
+
+use crate::{
+    optical::ray::Ray,
+    vectors::vector3::{Point3, Vector3},
+};
+
+use super::{
+    camera::{ImageSize, Rect},
+    camera_model::{image_uv, CameraModel, CameraView},
+    panoramic::spherical_direction,
+};
+
+/// How the images of both eyes are arranged in the output image.
+pub enum StereoLayout {
+    /// Left eye on the left half.
+    SideBySide,
+    /// Left eye on the top half.
+    TopBottom,
+}
+
+/// Projection of each eye.
+pub enum StereoProjection {
+    /// Off-axis projection: parallel eyes with frusta shifted to meet at the
+    /// convergence distance, where objects appear on the screen plane.
+    Perspective { vfov_deg: f32 },
+    /// Omnidirectional stereo: 360° equirectangular images with the eyes
+    /// on a circle, turning with the direction viewed. Use an aspect ratio
+    /// of 2.
+    Equirectangular,
+}
+
+/// Renders the images of a left and a right eye into one image.
+pub struct StereoCamera {
+    /// Position between the eyes and the view direction.
+    pub view: CameraView,
+    /// Size of the image of one eye.
+    pub eye_size: ImageSize,
+    pub projection: StereoProjection,
+    pub layout: StereoLayout,
+    /// Distance between the eyes in world units.
+    pub interocular_distance: f32,
+    /// Distance of the plane of zero parallax.
+    pub convergence_distance: f32,
+}
+
+impl StereoCamera {
+    /// Eye of the pixel (0 left, 1 right) and its position in the image of
+    /// that eye.
+    fn eye_pixel(&self, x: u32, y: u32) -> (u32, u32, u32) {
+        let eye = self.eye_size.rect();
+        match self.layout {
+            StereoLayout::SideBySide => (x / eye.width, x % eye.width, y),
+            StereoLayout::TopBottom => (y / eye.height, x, y % eye.height),
+        }
+    }
+
+    /// Ray from an eye offset `side` times half the interocular distance to
+    /// the right along `right`, aimed to converge with the ray of the other
+    /// eye along the `direction` from the center. Both in camera space.
+    fn eye_ray(&self, side: f32, right: Vector3, direction: Vector3) -> Ray {
+        let offset = right * (side * self.interocular_distance / 2.0);
+        let target = direction * self.convergence_distance;
+        let origin: Point3 = self.view.center + self.view.to_world(offset);
+        Ray::from((origin, self.view.to_world(target - offset).to_unit()))
+    }
+}
+
+impl CameraModel for StereoCamera {
+    fn image_rect(&self) -> Rect<u32> {
+        let eye = self.eye_size.rect();
+        match self.layout {
+            StereoLayout::SideBySide => Rect {
+                width: 2 * eye.width,
+                height: eye.height,
+            },
+            StereoLayout::TopBottom => Rect {
+                width: eye.width,
+                height: 2 * eye.height,
+            },
+        }
+    }
+
+    fn get_ray(
+        &self,
+        x: u32,
+        y: u32,
+        pixel_sample: (f32, f32),
+        _lens_sample: (f32, f32),
+    ) -> Option<Ray> {
+        let (eye, x, y) = self.eye_pixel(x, y);
+        let side = if eye == 0 { -1.0 } else { 1.0 };
+        let (u, v) = image_uv(self.eye_size.rect(), x, y, pixel_sample);
+        match self.projection {
+            StereoProjection::Perspective { vfov_deg } => {
+                // Point on the screen plane at unit distance; scaled to the
+                // convergence distance both eyes see it at the same place.
+                let rect = self.eye_size.rect();
+                let height = 2.0 * (vfov_deg.to_radians() / 2.0).tan();
+                let width = height * rect.width as f32 / rect.height as f32;
+                let direction = Vector3::from(((u - 0.5) * width, (0.5 - v) * height, -1.0));
+                Some(self.eye_ray(side, Vector3::from((1.0, 0.0, 0.0)), direction))
+            }
+            StereoProjection::Equirectangular => {
+                let longitude = (u - 0.5) * 2.0 * PI;
+                let latitude = (0.5 - v) * PI;
+                let direction = spherical_direction(longitude, latitude);
+                // Fade the eye separation out towards the poles, where the
+                // eye circle would otherwise swirl around the view direction.
+                let right = Vector3::from((longitude.cos(), 0.0, longitude.sin())) * latitude.cos();
+                Some(self.eye_ray(side, right, direction))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderers::camera::CameraGeometryParam;
+
+    fn camera(projection: StereoProjection, layout: StereoLayout) -> StereoCamera {
+        StereoCamera {
+            view: CameraView::from(CameraGeometryParam {
+                center: Point3::from((0.0, 0.0, 0.0)),
+                lookat: Point3::from((0.0, 0.0, -1.0)),
+                up: Vector3::from((0.0, 1.0, 0.0)),
+            }),
+            eye_size: ImageSize {
+                aspect_ratio: 2.0,
+                width: 32,
+            },
+            projection,
+            layout,
+            interocular_distance: 0.064,
+            convergence_distance: 2.0,
+        }
+    }
+
+    /// Point of the ray at the depth of the convergence plane.
+    fn at_convergence(ray: &Ray) -> Vector3 {
+        let t = (-2.0 - ray.origin.z) / ray.direction.z;
+        ray.origin + ray.direction * t
+    }
+
+    #[test]
+    fn should_converge_perspective_eyes_at_the_convergence_distance() {
+        let stereo = camera(
+            StereoProjection::Perspective { vfov_deg: 60.0 },
+            StereoLayout::SideBySide,
+        );
+        assert_eq!(
+            (stereo.image_rect().width, stereo.image_rect().height),
+            (64, 16)
+        );
+        let left = stereo.get_ray(3, 5, (0.5, 0.5), (0.5, 0.5)).unwrap();
+        let right = stereo.get_ray(32 + 3, 5, (0.5, 0.5), (0.5, 0.5)).unwrap();
+        assert!((left.origin.x + 0.032).abs() < 1e-6);
+        assert!((right.origin.x - 0.032).abs() < 1e-6);
+        assert!((at_convergence(&left) - at_convergence(&right)).norm() < 1e-4);
+    }
+
+    #[test]
+    fn should_converge_ods_eyes_at_the_convergence_distance() {
+        let ods = camera(StereoProjection::Equirectangular, StereoLayout::TopBottom);
+        assert_eq!((ods.image_rect().width, ods.image_rect().height), (32, 32));
+        let left = ods.get_ray(15, 7, (0.5, 0.5), (0.5, 0.5)).unwrap();
+        let right = ods.get_ray(15, 16 + 7, (0.5, 0.5), (0.5, 0.5)).unwrap();
+        assert!((left.origin - right.origin).norm() > 0.06);
+        assert!((at_convergence(&left) - at_convergence(&right)).norm() < 1e-3);
+    }
+
+    #[test]
+    fn should_fade_ods_eye_separation_towards_the_poles() {
+        let ods = camera(StereoProjection::Equirectangular, StereoLayout::TopBottom);
+        let separation = |y: u32, pixel_sample: (f32, f32)| {
+            let left = ods.get_ray(15, y, pixel_sample, (0.5, 0.5)).unwrap();
+            let right = ods.get_ray(15, 16 + y, pixel_sample, (0.5, 0.5)).unwrap();
+            (left.origin - right.origin).norm()
+        };
+        assert!(separation(7, (0.5, 0.5)) > separation(3, (0.5, 0.5)));
+        assert!(separation(3, (0.5, 0.5)) > separation(0, (0.5, 0.5)));
+        // top and bottom edge of the image
+        assert!(separation(0, (0.5, 0.0)) < 1e-6);
+        assert!(separation(15, (0.5, 1.0)) < 1e-6);
+    }
+}
@@ -0,0 +1,7 @@
+pub mod blue_noise;
+pub mod halton;
+pub mod independent;
+pub mod sampler;
+pub mod sobol;
+pub mod stratified;
+pub mod warp;
@@ -0,0 +1,171 @@
+use once_cell::sync::Lazy;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::sampler::{hash, Sampler};
+
+/// Side of the tileable blue noise texture.
+const SIZE: usize = 64;
+/// Standard deviation of the energy kernel of void-and-cluster in pixels.
+const SIGMA: f32 = 1.5;
+
+static RANKS: Lazy<Vec<f32>> = Lazy::new(|| void_and_cluster(SIZE, SIGMA, 0));
+
+/// Tileable blue noise dither texture by Ulichney's void-and-cluster
+/// method: each pixel gets its rank in an ordering where every prefix is
+/// evenly spread out. Returns the ranks normalized to [0, 1).
+fn void_and_cluster(size: usize, sigma: f32, seed: u64) -> Vec<f32> {
+    let n = size * size;
+    // toroidal Gaussian energy for each offset
+    let kernel: Vec<f32> = (0..n)
+        .map(|i| {
+            let wrap = |d: usize| d.min(size - d) as f32;
+            let (dx, dy) = (wrap(i % size), wrap(i / size));
+            (-(dx * dx + dy * dy) / (2.0 * sigma * sigma)).exp()
+        })
+        .collect();
+    let mut energy = vec![0.0f32; n];
+    let mut on = vec![false; n];
+    let update = |energy: &mut Vec<f32>, p: usize, sign: f32| {
+        let (px, py) = (p % size, p / size);
+        for (q, e) in energy.iter_mut().enumerate() {
+            let dx = (q % size + size - px) % size;
+            let dy = (q / size + size - py) % size;
+            *e += sign * kernel[dy * size + dx];
+        }
+    };
+    // tightest cluster among the set pixels, largest void among the others
+    let tightest = |energy: &Vec<f32>, on: &Vec<bool>| {
+        (0..n)
+            .filter(|p| on[*p])
+            .max_by(|a, b| energy[*a].total_cmp(&energy[*b]))
+            .unwrap()
+    };
+    let largest_void = |energy: &Vec<f32>, on: &Vec<bool>| {
+        (0..n)
+            .filter(|p| !on[*p])
+            .min_by(|a, b| energy[*a].total_cmp(&energy[*b]))
+            .unwrap()
+    };
+
+    // initial binary pattern, relaxed until evenly spread
+    let mut rng = StdRng::seed_from_u64(seed);
+    let initial = n / 10;
+    let mut count = 0;
+    while count < initial {
+        let p = rng.gen_range(0..n);
+        if !on[p] {
+            on[p] = true;
+            update(&mut energy, p, 1.0);
+            count += 1;
+        }
+    }
+    loop {
+        let cluster = tightest(&energy, &on);
+        on[cluster] = false;
+        update(&mut energy, cluster, -1.0);
+        let void = largest_void(&energy, &on);
+        if void == cluster {
+            on[cluster] = true;
+            update(&mut energy, cluster, 1.0);
+            break;
+        }
+        on[void] = true;
+        update(&mut energy, void, 1.0);
+    }
+
+    let mut ranks = vec![0usize; n];
+    // ranks below the initial pattern by removing clusters
+    let (initial_on, initial_energy) = (on.clone(), energy.clone());
+    for rank in (0..initial).rev() {
+        let cluster = tightest(&energy, &on);
+        on[cluster] = false;
+        update(&mut energy, cluster, -1.0);
+        ranks[cluster] = rank;
+    }
+    // ranks above it by filling voids
+    let (mut on, mut energy) = (initial_on, initial_energy);
+    for rank in initial..n {
+        let void = largest_void(&energy, &on);
+        on[void] = true;
+        update(&mut energy, void, 1.0);
+        ranks[void] = rank;
+    }
+    ranks
+        .into_iter()
+        .map(|rank| (rank as f32 + 0.5) / n as f32)
+        .collect()
+}
+
+/// Value of the blue noise texture, tiled over the plane.
+fn blue_noise(x: u32, y: u32) -> f32 {
+    let (x, y) = (x as usize % SIZE, y as usize % SIZE);
+    RANKS[y * SIZE + x]
+}
+
+/// Generalized golden ratios of the R1 and R2 low discrepancy sequences.
+const R1: f32 = 0.618_034;
+const R2: (f32, f32) = (0.754_877_7, 0.569_840_3);
+
+/// Blue noise dithered sampling: each dimension starts from the value of a
+/// blue noise texture (shifted per dimension) and advances along a
+/// low-discrepancy sequence per sample. Neighboring pixels get very
+/// different samples, so the remaining error looks like fine, high
+/// frequency noise rather than clumps.
+pub struct BlueNoiseSampler {
+    pub seed: u32,
+    pixel: (u32, u32),
+    index: u32,
+    dimension: u32,
+}
+
+impl BlueNoiseSampler {
+    pub fn new(seed: u32) -> Self {
+        BlueNoiseSampler {
+            seed,
+            pixel: (0, 0),
+            index: 0,
+            dimension: 0,
+        }
+    }
+
+    fn dither(&self, salt: u32) -> f32 {
+        let offset = hash(&[self.seed, self.dimension, salt]);
+        blue_noise(
+            self.pixel.0.wrapping_add(offset),
+            self.pixel.1.wrapping_add(offset >> 16),
+        )
+    }
+}
+
+fn fract(value: f32) -> f32 {
+    (value - value.floor()).min(1.0 - f32::EPSILON)
+}
+
+impl Sampler for BlueNoiseSampler {
+    fn start_pixel_sample(&mut self, pixel: (u32, u32), index: u32) {
+        self.pixel = pixel;
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    fn get_1d(&mut self) -> f32 {
+        let value = fract(self.dither(0) + R1 * self.index as f32);
+        self.dimension += 1;
+        value
+    }
+
+    fn get_2d(&mut self) -> (f32, f32) {
+        let x = fract(self.dither(0) + R2.0 * self.index as f32);
+        let y = fract(self.dither(1) + R2.1 * self.index as f32);
+        self.dimension += 1;
+        (x, y)
+    }
+
+    fn clone_box(&self) -> Box<dyn Sampler> {
+        Box::new(BlueNoiseSampler::new(self.seed))
+    }
+
+    fn describe(&self) -> String {
+        format!("blue_noise seed={}", self.seed)
+    }
+}
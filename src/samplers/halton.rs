@@ -0,0 +1,88 @@
+use super::sampler::{hash, to_unit_float, Sampler};
+
+const PRIMES: [u32; 32] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+    101, 103, 107, 109, 113, 127, 131,
+];
+
+/// Radical inverse of `index` in `base`: its digits mirrored around the
+/// decimal point.
+pub fn radical_inverse(base: u32, mut index: u32) -> f32 {
+    let inverse_base = 1.0 / base as f64;
+    let mut factor = inverse_base;
+    let mut value = 0.0;
+    while index > 0 {
+        value += (index % base) as f64 * factor;
+        index /= base;
+        factor *= inverse_base;
+    }
+    value as f32
+}
+
+/// Halton sequence: dimension i is the radical inverse in the i-th prime.
+/// Each pixel gets a random toroidal shift per dimension (Cranley-Patterson
+/// rotation) so neighboring pixels do not share the same samples.
+/// Dimensions beyond the prime table fall back to hashed values of the
+/// pixel, sample and dimension, which stay deterministic.
+pub struct HaltonSampler {
+    pub seed: u32,
+    pixel: (u32, u32),
+    index: u32,
+    dimension: u32,
+}
+
+impl HaltonSampler {
+    pub fn new(seed: u32) -> Self {
+        HaltonSampler {
+            seed,
+            pixel: (0, 0),
+            index: 0,
+            dimension: 0,
+        }
+    }
+}
+
+impl Sampler for HaltonSampler {
+    fn start_pixel_sample(&mut self, pixel: (u32, u32), index: u32) {
+        self.pixel = pixel;
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    fn get_1d(&mut self) -> f32 {
+        let dimension = self.dimension;
+        self.dimension += 1;
+        let Some(base) = PRIMES.get(dimension as usize) else {
+            let (x, y) = self.pixel;
+            return to_unit_float(hash(&[self.seed, x, y, self.index, dimension]));
+        };
+        let shift = to_unit_float(hash(&[self.seed, self.pixel.0, self.pixel.1, dimension]));
+        let value = radical_inverse(*base, self.index) + shift;
+        (value - value.floor()).min(1.0 - f32::EPSILON)
+    }
+
+    fn clone_box(&self) -> Box<dyn Sampler> {
+        Box::new(HaltonSampler::new(self.seed))
+    }
+
+    fn describe(&self) -> String {
+        format!("halton seed={}", self.seed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_repeat_dimensions_beyond_the_prime_table() {
+        let draw = || {
+            let mut sampler = HaltonSampler::new(7);
+            sampler.start_pixel_sample((3, 4), 5);
+            (0..40).map(|_| sampler.get_1d()).collect::<Vec<f32>>()
+        };
+        let values = draw();
+        assert_eq!(values, draw());
+        assert!(values.iter().all(|v| (0.0..1.0).contains(v)));
+    }
+}
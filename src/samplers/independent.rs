@@ -0,0 +1,24 @@
+use super::sampler::Sampler;
+
+/// Independent uniform random numbers, the baseline.
+pub struct IndependentSampler;
+
+impl Sampler for IndependentSampler {
+    fn start_pixel_sample(&mut self, _pixel: (u32, u32), _index: u32) {}
+
+    fn get_1d(&mut self) -> f32 {
+        rand::random()
+    }
+
+    fn clone_box(&self) -> Box<dyn Sampler> {
+        Box::new(IndependentSampler)
+    }
+
+    fn describe(&self) -> String {
+        "independent".to_string()
+    }
+
+    fn is_deterministic(&self) -> bool {
+        false
+    }
+}
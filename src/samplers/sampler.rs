@@ -0,0 +1,84 @@
+use std::cell::RefCell;
+
+/// Source of the random numbers of a camera sample, one dimension at a
+/// time. Well distributed samplers place the samples of a pixel evenly in
+/// each dimension, which converges faster than independent random numbers.
+pub trait Sampler: Send {
+    /// Start sample `index` of the pixel. Dimensions restart from the first.
+    fn start_pixel_sample(&mut self, pixel: (u32, u32), index: u32);
+
+    /// Next dimension, in [0, 1).
+    fn get_1d(&mut self) -> f32;
+
+    /// Next two dimensions, in [0, 1)^2, well distributed as a pair.
+    fn get_2d(&mut self) -> (f32, f32) {
+        (self.get_1d(), self.get_1d())
+    }
+
+    /// Copy of the sampler for another sample, e.g. on another thread.
+    fn clone_box(&self) -> Box<dyn Sampler>;
+
+    /// Kind and parameters of the sampler, e.g. to check that a checkpoint
+    /// is resumed with the same sampler.
+    fn describe(&self) -> String;
+
+    /// Whether the samples only depend on the pixel, the sample index and
+    /// the dimension, so a resumed render continues the same sequences.
+    fn is_deterministic(&self) -> bool {
+        true
+    }
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<Box<dyn Sampler>>> = RefCell::new(None);
+}
+
+/// Draw the samples of `sample_1d` and `sample_2d` from `sampler` on this
+/// thread while running `f`.
+pub fn with_sampler<R>(sampler: Box<dyn Sampler>, f: impl FnOnce() -> R) -> R {
+    let previous = CURRENT.with(|current| current.replace(Some(sampler)));
+    let result = f();
+    CURRENT.with(|current| current.replace(previous));
+    result
+}
+
+/// Next dimension of the current sampler of the thread, uniform random if
+/// there is none. For sampling inside of the renderer, e.g. in
+/// `Material::scatter`.
+pub fn sample_1d() -> f32 {
+    CURRENT.with(|current| match current.borrow_mut().as_mut() {
+        Some(sampler) => sampler.get_1d(),
+        None => rand::random(),
+    })
+}
+
+/// Next two dimensions of the current sampler of the thread (see
+/// `sample_1d`).
+pub fn sample_2d() -> (f32, f32) {
+    CURRENT.with(|current| match current.borrow_mut().as_mut() {
+        Some(sampler) => sampler.get_2d(),
+        None => (rand::random(), rand::random()),
+    })
+}
+
+/// Hash of the values, for decorrelating pixels and dimensions.
+pub fn hash(values: &[u32]) -> u32 {
+    // FNV-1a followed by a murmur3 style finalizer
+    let mut h: u32 = 0x811c_9dc5;
+    for value in values {
+        for byte in value.to_le_bytes() {
+            h ^= byte as u32;
+            h = h.wrapping_mul(0x0100_0193);
+        }
+    }
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x85eb_ca6b);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0xc2b2_ae35);
+    h ^ (h >> 16)
+}
+
+/// Map 32 random bits to [0, 1).
+pub fn to_unit_float(bits: u32) -> f32 {
+    (bits >> 8) as f32 * (1.0 / (1u32 << 24) as f32)
+}
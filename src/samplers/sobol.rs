@@ -0,0 +1,123 @@
+use super::sampler::{hash, to_unit_float, Sampler};
+
+/// First dimension of the Sobol sequence: the bits of the index mirrored.
+fn sobol_0(index: u32) -> u32 {
+    index.reverse_bits()
+}
+
+/// Second dimension of the Sobol sequence (primitive polynomial x + 1).
+fn sobol_1(mut index: u32) -> u32 {
+    let mut v: u32 = 1 << 31;
+    let mut result = 0;
+    while index > 0 {
+        if index & 1 == 1 {
+            result ^= v;
+        }
+        index >>= 1;
+        v ^= v >> 1;
+    }
+    result
+}
+
+/// Hash that only mixes bits upwards (Laine and Karras).
+fn laine_karras_permutation(mut x: u32, seed: u32) -> u32 {
+    x = x.wrapping_add(seed);
+    x ^= x.wrapping_mul(0x6c50_b47c);
+    x ^= x.wrapping_mul(0xb82f_1e52);
+    x ^= x.wrapping_mul(0xc7af_e638);
+    x ^= x.wrapping_mul(0x8d22_f6e6);
+    x
+}
+
+/// Owen scrambling of the bits of `x` (Burley, "Practical hash-based Owen
+/// scrambling").
+fn nested_uniform_scramble(x: u32, seed: u32) -> u32 {
+    laine_karras_permutation(x.reverse_bits(), seed).reverse_bits()
+}
+
+/// Owen scrambled Sobol sequence. Each pair of dimensions uses the first two
+/// Sobol dimensions, a (0, 2)-sequence, with its own scrambling and
+/// shuffled index (padding), so any number of dimensions is well
+/// distributed as pairs.
+pub struct SobolSampler {
+    pub seed: u32,
+    pixel: (u32, u32),
+    index: u32,
+    dimension: u32,
+}
+
+impl SobolSampler {
+    pub fn new(seed: u32) -> Self {
+        SobolSampler {
+            seed,
+            pixel: (0, 0),
+            index: 0,
+            dimension: 0,
+        }
+    }
+
+    fn dimension_seed(&self, salt: u32) -> u32 {
+        hash(&[self.seed, self.pixel.0, self.pixel.1, self.dimension, salt])
+    }
+
+    fn shuffled_index(&self) -> u32 {
+        nested_uniform_scramble(self.index, self.dimension_seed(0))
+    }
+}
+
+impl Sampler for SobolSampler {
+    fn start_pixel_sample(&mut self, pixel: (u32, u32), index: u32) {
+        self.pixel = pixel;
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    fn get_1d(&mut self) -> f32 {
+        let index = self.shuffled_index();
+        let x = nested_uniform_scramble(sobol_0(index), self.dimension_seed(1));
+        self.dimension += 1;
+        to_unit_float(x)
+    }
+
+    fn get_2d(&mut self) -> (f32, f32) {
+        let index = self.shuffled_index();
+        let x = nested_uniform_scramble(sobol_0(index), self.dimension_seed(1));
+        let y = nested_uniform_scramble(sobol_1(index), self.dimension_seed(2));
+        self.dimension += 1;
+        (to_unit_float(x), to_unit_float(y))
+    }
+
+    fn clone_box(&self) -> Box<dyn Sampler> {
+        Box::new(SobolSampler::new(self.seed))
+    }
+
+    fn describe(&self) -> String {
+        format!("sobol seed={}", self.seed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_stratify_power_of_two_samples() {
+        // A (0, 2)-sequence puts one point of the first 16 in each cell of
+        // every 16-cell elementary grid, also after scrambling.
+        let mut sampler = SobolSampler::new(7);
+        let points: Vec<(f32, f32)> = (0..16)
+            .map(|i| {
+                sampler.start_pixel_sample((3, 5), i);
+                sampler.get_2d()
+            })
+            .collect();
+        for (nx, ny) in [(16, 1), (8, 2), (4, 4), (2, 8), (1, 16)] {
+            let mut cells = [0; 16];
+            for (x, y) in &points {
+                let cell = (x * nx as f32) as usize + nx * (y * ny as f32) as usize;
+                cells[cell] += 1;
+            }
+            assert!(cells.iter().all(|count| *count == 1), "{}x{}", nx, ny);
+        }
+    }
+}
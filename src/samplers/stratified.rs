@@ -0,0 +1,123 @@
+use super::sampler::{hash, to_unit_float, Sampler};
+
+/// Jittered stratified sampling: the samples of a pixel fall into distinct
+/// strata of each dimension (a grid for pairs of dimensions), at a random
+/// position inside of their stratum. Strata are shuffled per pixel and
+/// dimension so dimensions stay uncorrelated.
+pub struct StratifiedSampler {
+    /// Number of strata, the samples per pixel it is designed for.
+    pub samples_per_pixel: u32,
+    pub seed: u32,
+    pixel: (u32, u32),
+    index: u32,
+    dimension: u32,
+}
+
+impl StratifiedSampler {
+    pub fn new(samples_per_pixel: u32, seed: u32) -> Self {
+        StratifiedSampler {
+            samples_per_pixel: samples_per_pixel.max(1),
+            seed,
+            pixel: (0, 0),
+            index: 0,
+            dimension: 0,
+        }
+    }
+
+    /// Stratum of the sample in the current dimension, a random permutation
+    /// of the sample index. Samples beyond `samples_per_pixel` start a new
+    /// round with another permutation.
+    fn stratum(&self, strata: u32) -> u32 {
+        let round = self.index / strata;
+        let key = [self.seed, self.pixel.0, self.pixel.1, self.dimension, round];
+        permute(self.index % strata, strata, hash(&key))
+    }
+
+    fn jitter(&self, salt: u32) -> f32 {
+        to_unit_float(hash(&[
+            self.seed,
+            self.pixel.0,
+            self.pixel.1,
+            self.dimension,
+            self.index,
+            salt,
+        ]))
+    }
+}
+
+/// Element `index` of a random permutation of 0..n (Kensler, "Correlated
+/// multi-jittered sampling").
+pub fn permute(index: u32, n: u32, seed: u32) -> u32 {
+    if n <= 1 {
+        return 0;
+    }
+    let mut w = n - 1;
+    w |= w >> 1;
+    w |= w >> 2;
+    w |= w >> 4;
+    w |= w >> 8;
+    w |= w >> 16;
+    let mut i = index;
+    loop {
+        i ^= seed;
+        i = i.wrapping_mul(0xe170_893d);
+        i ^= seed >> 16;
+        i ^= (i & w) >> 4;
+        i ^= seed >> 8;
+        i = i.wrapping_mul(0x0929_eb3f);
+        i ^= seed >> 23;
+        i ^= (i & w) >> 1;
+        i = i.wrapping_mul(1 | seed >> 27);
+        i = i.wrapping_mul(0x6935_fa69);
+        i ^= (i & w) >> 11;
+        i = i.wrapping_mul(0x74dc_b303);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0x9e50_1cc3);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0xc860_a3df);
+        i &= w;
+        i ^= i >> 5;
+        if i < n {
+            break;
+        }
+    }
+    (i.wrapping_add(seed)) % n
+}
+
+impl Sampler for StratifiedSampler {
+    fn start_pixel_sample(&mut self, pixel: (u32, u32), index: u32) {
+        self.pixel = pixel;
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    fn get_1d(&mut self) -> f32 {
+        let strata = self.samples_per_pixel;
+        let stratum = self.stratum(strata);
+        let value = (stratum as f32 + self.jitter(0)) / strata as f32;
+        self.dimension += 1;
+        value.min(1.0 - f32::EPSILON)
+    }
+
+    fn get_2d(&mut self) -> (f32, f32) {
+        // as square a grid as possible
+        let nx = (self.samples_per_pixel as f32).sqrt().ceil() as u32;
+        let ny = self.samples_per_pixel.div_ceil(nx);
+        let stratum = self.stratum(nx * ny);
+        let x = ((stratum % nx) as f32 + self.jitter(0)) / nx as f32;
+        let y = ((stratum / nx) as f32 + self.jitter(1)) / ny as f32;
+        self.dimension += 1;
+        (x.min(1.0 - f32::EPSILON), y.min(1.0 - f32::EPSILON))
+    }
+
+    fn clone_box(&self) -> Box<dyn Sampler> {
+        Box::new(StratifiedSampler::new(self.samples_per_pixel, self.seed))
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "stratified samples_per_pixel={} seed={}",
+            self.samples_per_pixel, self.seed
+        )
+    }
+}
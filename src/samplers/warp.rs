@@ -0,0 +1,32 @@
+use std::f32::consts::PI;
+
+use crate::vectors::vector3::Vector3;
+
+/// Uniformly distributed point on the unit sphere.
+pub fn unit_sphere(u: (f32, f32)) -> Vector3 {
+    let z = 1.0 - 2.0 * u.0;
+    let r = (1.0 - z * z).max(0.0).sqrt();
+    let phi = 2.0 * PI * u.1;
+    Vector3::from((r * phi.cos(), r * phi.sin(), z))
+}
+
+/// Uniformly distributed point in the unit disk (z = 0), by the concentric
+/// mapping of the square which keeps strata compact.
+pub fn unit_disk(u: (f32, f32)) -> Vector3 {
+    let (a, b) = (2.0 * u.0 - 1.0, 2.0 * u.1 - 1.0);
+    if a == 0.0 && b == 0.0 {
+        return Vector3::zero();
+    }
+    let (r, phi) = if a.abs() > b.abs() {
+        (a, PI / 4.0 * (b / a))
+    } else {
+        (b, PI / 2.0 - PI / 4.0 * (a / b))
+    };
+    Vector3::from((r * phi.cos(), r * phi.sin(), 0.0))
+}
+
+/// Cosine distributed direction in the hemisphere around +z.
+pub fn cosine_hemisphere(u: (f32, f32)) -> Vector3 {
+    let d = unit_disk(u);
+    Vector3::from((d.x, d.y, (1.0 - d.x * d.x - d.y * d.y).max(0.0).sqrt()))
+}
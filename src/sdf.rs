@@ -0,0 +1,3 @@
+pub mod distance;
+pub mod operators;
+pub mod primitives;
@@ -0,0 +1,15 @@
+use crate::vectors::vector3::Point3;
+
+/// Signed distance function: negative inside of the shape, positive outside.
+/// Must not overestimate the distance to the surface (Lipschitz bound 1),
+/// otherwise sphere tracing may step through the surface.
+pub trait SignedDistance: Sync {
+    fn distance(&self, point: Point3) -> f32;
+}
+
+/// Allow any user supplied distance function to be used as SDF.
+impl<F: Fn(Point3) -> f32 + Sync> SignedDistance for F {
+    fn distance(&self, point: Point3) -> f32 {
+        self(point)
+    }
+}
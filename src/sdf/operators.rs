@@ -0,0 +1,172 @@
+use crate::vectors::vector3::{Point3, Vector3};
+
+use super::distance::SignedDistance;
+
+type BoxedSdf = Box<dyn SignedDistance + Send>;
+
+pub struct Union {
+    pub a: BoxedSdf,
+    pub b: BoxedSdf,
+}
+
+impl SignedDistance for Union {
+    fn distance(&self, point: Point3) -> f32 {
+        self.a.distance(point).min(self.b.distance(point))
+    }
+}
+
+/// Union blending both shapes within distance `k` (polynomial smooth min).
+pub struct SmoothUnion {
+    pub a: BoxedSdf,
+    pub b: BoxedSdf,
+    pub k: f32,
+}
+
+impl SignedDistance for SmoothUnion {
+    fn distance(&self, point: Point3) -> f32 {
+        let d1 = self.a.distance(point);
+        let d2 = self.b.distance(point);
+        if self.k <= 0.0 {
+            return d1.min(d2);
+        }
+        let h = (0.5 + 0.5 * (d2 - d1) / self.k).clamp(0.0, 1.0);
+        d2 + (d1 - d2) * h - self.k * h * (1.0 - h)
+    }
+}
+
+pub struct Subtraction {
+    /// Shape to cut from.
+    pub a: BoxedSdf,
+    /// Shape to cut out.
+    pub b: BoxedSdf,
+}
+
+impl SignedDistance for Subtraction {
+    fn distance(&self, point: Point3) -> f32 {
+        self.a.distance(point).max(-self.b.distance(point))
+    }
+}
+
+pub struct Translate {
+    pub sdf: BoxedSdf,
+    pub offset: Vector3,
+}
+
+impl SignedDistance for Translate {
+    fn distance(&self, point: Point3) -> f32 {
+        self.sdf.distance(point - self.offset)
+    }
+}
+
+/// Infinite repetition of the shape with the given period per axis.
+/// A period of zero (or less) disables repetition along that axis.
+/// The shape should fit into a single cell centered on the origin.
+pub struct Repetition {
+    pub sdf: BoxedSdf,
+    pub period: Vector3,
+}
+
+impl SignedDistance for Repetition {
+    fn distance(&self, point: Point3) -> f32 {
+        let mut p = point;
+        for axis in 0..3 {
+            let period = self.period[axis];
+            if period > 0.0 {
+                p[axis] -= period * (p[axis] / period).round();
+            }
+        }
+        self.sdf.distance(p)
+    }
+}
+
+/// Twist the shape around the y axis by `rate` radians per unit height.
+/// Twisting distorts distances, so the result is scaled down by a
+/// conservative Lipschitz bound given the radius `bound_r` of the shape.
+pub struct Twist {
+    pub sdf: BoxedSdf,
+    pub rate: f32,
+    pub bound_r: f32,
+}
+
+impl SignedDistance for Twist {
+    fn distance(&self, point: Point3) -> f32 {
+        let angle = self.rate * point.y;
+        let (sin, cos) = angle.sin_cos();
+        let p = Vector3::from((
+            cos * point.x - sin * point.z,
+            point.y,
+            sin * point.x + cos * point.z,
+        ));
+        let lipschitz = (1.0 + (self.rate * self.bound_r).powi(2)).sqrt();
+        self.sdf.distance(p) / lipschitz
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sdf::primitives::{SdfBox, SdfSphere};
+
+    fn sphere(x: f32, r: f32) -> BoxedSdf {
+        Box::new(SdfSphere {
+            center: Point3::from((x, 0.0, 0.0)),
+            r,
+        })
+    }
+
+    #[test]
+    fn should_blend_smooth_union_only_near_both_shapes() {
+        let union = Union {
+            a: sphere(-1.5, 1.0),
+            b: sphere(1.5, 1.0),
+        };
+        let smooth = SmoothUnion {
+            a: sphere(-1.5, 1.0),
+            b: sphere(1.5, 1.0),
+            k: 0.5,
+        };
+        // far from the other shape both agree
+        let point = Point3::from((3.0, 0.0, 0.0));
+        assert_eq!(smooth.distance(point), union.distance(point));
+        // halfway between, both spheres are 0.5 away and the blend fills
+        // in k / 4
+        let point = Point3::zero();
+        assert!((union.distance(point) - 0.5).abs() < 1e-6);
+        assert!((smooth.distance(point) - 0.375).abs() < 1e-6);
+    }
+
+    #[test]
+    fn should_repeat_along_axes_with_a_period() {
+        let repetition = Repetition {
+            sdf: sphere(0.0, 0.5),
+            period: Vector3::from((2.0, 0.0, 2.0)),
+        };
+        // centers of the copies
+        assert!((repetition.distance(Point3::from((4.0, 0.0, -2.0))) + 0.5).abs() < 1e-6);
+        // halfway between two copies
+        assert!((repetition.distance(Point3::from((1.0, 0.0, 0.0))) - 0.5).abs() < 1e-6);
+        // no repetition along y
+        assert!((repetition.distance(Point3::from((0.0, 3.0, 0.0))) - 2.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn should_twist_around_the_y_axis() {
+        let rate = std::f32::consts::FRAC_PI_2;
+        let twist = Twist {
+            sdf: Box::new(SdfBox {
+                center: Point3::zero(),
+                half_size: Vector3::from((2.0, 5.0, 0.5)),
+            }),
+            rate,
+            bound_r: 2.0,
+        };
+        let lipschitz = (1.0 + (rate * 2.0).powi(2)).sqrt();
+        // untwisted at y = 0
+        let distance = twist.distance(Point3::from((0.0, 0.0, 1.5)));
+        assert!((distance - 1.0 / lipschitz).abs() < 1e-5);
+        // a quarter turn at y = 1 swaps the long and the short side
+        assert!(twist.distance(Point3::from((0.0, 1.0, 1.5))) < 0.0);
+        let distance = twist.distance(Point3::from((1.5, 1.0, 0.0)));
+        assert!((distance - 1.0 / lipschitz).abs() < 1e-5);
+    }
+}
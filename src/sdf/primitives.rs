@@ -0,0 +1,83 @@
+use crate::vectors::{
+    ops::MatrixDot,
+    vector3::{Point3, Vector3},
+};
+
+use super::distance::SignedDistance;
+
+// Distance functions are adapted from
+// https://iquilezles.org/articles/distfunctions/
+
+pub struct SdfSphere {
+    pub center: Point3,
+    pub r: f32,
+}
+
+impl SignedDistance for SdfSphere {
+    fn distance(&self, point: Point3) -> f32 {
+        (point - self.center).norm() - self.r
+    }
+}
+
+/// Axis aligned box with half extents `half_size`.
+pub struct SdfBox {
+    pub center: Point3,
+    pub half_size: Vector3,
+}
+
+impl SignedDistance for SdfBox {
+    fn distance(&self, point: Point3) -> f32 {
+        let p = point - self.center;
+        let q = Vector3::from((
+            p.x.abs() - self.half_size.x,
+            p.y.abs() - self.half_size.y,
+            p.z.abs() - self.half_size.z,
+        ));
+        let outside = Vector3::from((q.x.max(0.0), q.y.max(0.0), q.z.max(0.0))).norm();
+        let inside = q.x.max(q.y).max(q.z).min(0.0);
+        outside + inside
+    }
+}
+
+/// Torus lying on the xz plane.
+pub struct SdfTorus {
+    pub center: Point3,
+    pub major_r: f32,
+    pub minor_r: f32,
+}
+
+impl SignedDistance for SdfTorus {
+    fn distance(&self, point: Point3) -> f32 {
+        let p = point - self.center;
+        let q_x = (p.x.powi(2) + p.z.powi(2)).sqrt() - self.major_r;
+        (q_x.powi(2) + p.y.powi(2)).sqrt() - self.minor_r
+    }
+}
+
+/// Capsule around the segment `a`-`b`.
+pub struct SdfCapsule {
+    pub a: Point3,
+    pub b: Point3,
+    pub r: f32,
+}
+
+impl SignedDistance for SdfCapsule {
+    fn distance(&self, point: Point3) -> f32 {
+        let pa = point - self.a;
+        let ba = self.b - self.a;
+        let h = (pa.dot(&ba) / ba.norm_squared()).clamp(0.0, 1.0);
+        (pa - ba * h).norm() - self.r
+    }
+}
+
+/// Half space below the plane through `origin` with unit `normal`.
+pub struct SdfPlane {
+    pub origin: Point3,
+    pub normal: Vector3,
+}
+
+impl SignedDistance for SdfPlane {
+    fn distance(&self, point: Point3) -> f32 {
+        (point - self.origin).dot(&self.normal)
+    }
+}
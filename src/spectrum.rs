@@ -0,0 +1,3 @@
+pub mod cie;
+pub mod sampled;
+pub mod upsampling;
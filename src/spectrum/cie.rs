@@ -0,0 +1,58 @@
+use once_cell::sync::Lazy;
+
+use crate::color::Color;
+
+use super::sampled::{LAMBDA_MAX, LAMBDA_MIN};
+
+/// Piecewise gaussian with different widths on each side of the mean.
+fn piecewise_gaussian(x: f32, mu: f32, sigma_low: f32, sigma_high: f32) -> f32 {
+    let sigma = if x < mu { sigma_low } else { sigma_high };
+    (-0.5 * ((x - mu) / sigma).powi(2)).exp()
+}
+
+/// CIE 1931 color matching functions at the wavelength (nm), using the
+/// multi-lobe fit of Wyman et al. 2013, "Simple Analytic Approximations to
+/// the CIE XYZ Color Matching Functions".
+pub fn xyz_matching(lambda: f32) -> Color {
+    Color::from((
+        1.056 * piecewise_gaussian(lambda, 599.8, 37.9, 31.0)
+            + 0.362 * piecewise_gaussian(lambda, 442.0, 16.0, 26.7)
+            - 0.065 * piecewise_gaussian(lambda, 501.1, 20.4, 26.2),
+        0.821 * piecewise_gaussian(lambda, 568.8, 46.9, 40.5)
+            + 0.286 * piecewise_gaussian(lambda, 530.9, 16.3, 31.1),
+        1.217 * piecewise_gaussian(lambda, 437.0, 11.8, 36.0)
+            + 0.681 * piecewise_gaussian(lambda, 459.0, 26.0, 13.8),
+    ))
+}
+
+/// Integral of the matching functions over the sampled range, in 1nm steps.
+static CIE_INTEGRAL: Lazy<Color> = Lazy::new(|| {
+    let mut integral = Color::zero();
+    let mut lambda = LAMBDA_MIN;
+    while lambda <= LAMBDA_MAX {
+        integral += xyz_matching(lambda);
+        lambda += 1.0;
+    }
+    integral
+});
+
+pub fn cie_y_integral() -> f32 {
+    CIE_INTEGRAL.y
+}
+
+fn xyz_to_linear_srgb(xyz: &Color) -> Color {
+    Color::from((
+        3.2406 * xyz.x - 1.5372 * xyz.y - 0.4986 * xyz.z,
+        -0.9689 * xyz.x + 1.8758 * xyz.y + 0.0415 * xyz.z,
+        0.0557 * xyz.x - 0.2040 * xyz.y + 1.0570 * xyz.z,
+    ))
+}
+
+/// Linear sRGB of a constant spectrum of value 1, used for white balancing.
+static WHITE: Lazy<Color> = Lazy::new(|| xyz_to_linear_srgb(&(*CIE_INTEGRAL / CIE_INTEGRAL.y)));
+
+/// Convert XYZ to linear sRGB, white balanced so that a constant spectrum
+/// (e.g. upsampled from RGB white) maps back to white.
+pub fn xyz_to_rgb(xyz: &Color) -> Color {
+    xyz_to_linear_srgb(xyz) / *WHITE
+}
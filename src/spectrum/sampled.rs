@@ -0,0 +1,121 @@
+use std::ops;
+
+use crate::color::Color;
+
+use super::{cie, upsampling::rgb_to_spectrum};
+
+/// Number of wavelengths carried by each path.
+pub const N_SAMPLES: usize = 4;
+pub const LAMBDA_MIN: f32 = 360.0;
+pub const LAMBDA_MAX: f32 = 830.0;
+
+/// Wavelengths (in nm) carried by a path, sampled with hero wavelength
+/// sampling: the hero is uniformly distributed and the others are rotated
+/// by equal offsets over the visible range.
+/// https://doi.org/10.1111/cgf.12419
+pub struct SampledWavelengths {
+    pub lambda: [f32; N_SAMPLES],
+    pub pdf: [f32; N_SAMPLES],
+}
+
+impl SampledWavelengths {
+    pub fn sample_uniform(u: f32) -> Self {
+        let range = LAMBDA_MAX - LAMBDA_MIN;
+        let mut lambda = [0.0; N_SAMPLES];
+        lambda[0] = LAMBDA_MIN + u * range;
+        let delta = range / N_SAMPLES as f32;
+        for i in 1..N_SAMPLES {
+            lambda[i] = lambda[i - 1] + delta;
+            if lambda[i] > LAMBDA_MAX {
+                lambda[i] -= range;
+            }
+        }
+        SampledWavelengths {
+            lambda,
+            pdf: [1.0 / range; N_SAMPLES],
+        }
+    }
+
+    pub fn hero(&self) -> f32 {
+        self.lambda[0]
+    }
+
+    /// Drop all but the hero wavelength, e.g. after dispersion made the path
+    /// depend on the wavelength.
+    pub fn terminate_secondary(&mut self) {
+        if self.secondary_terminated() {
+            return;
+        }
+        for i in 1..N_SAMPLES {
+            self.pdf[i] = 0.0;
+        }
+        self.pdf[0] /= N_SAMPLES as f32;
+    }
+
+    pub fn secondary_terminated(&self) -> bool {
+        self.pdf[1..].iter().all(|pdf| *pdf == 0.0)
+    }
+}
+
+/// Spectral quantity evaluated at `SampledWavelengths`.
+pub struct SampledSpectrum {
+    pub values: [f32; N_SAMPLES],
+}
+
+impl SampledSpectrum {
+    pub fn constant(value: f32) -> Self {
+        SampledSpectrum {
+            values: [value; N_SAMPLES],
+        }
+    }
+
+    /// Upsample a RGB color to a spectrum and evaluate it at the wavelengths.
+    pub fn from_rgb(color: &Color, wavelengths: &SampledWavelengths) -> Self {
+        let mut values = [0.0; N_SAMPLES];
+        for (value, lambda) in values.iter_mut().zip(wavelengths.lambda) {
+            *value = rgb_to_spectrum(color, lambda);
+        }
+        SampledSpectrum { values }
+    }
+
+    /// Monte Carlo estimate of the linear sRGB color of the spectrum.
+    pub fn to_rgb(self, wavelengths: &SampledWavelengths) -> Color {
+        let mut xyz = Color::zero();
+        for i in 0..N_SAMPLES {
+            if wavelengths.pdf[i] > 0.0 {
+                xyz += cie::xyz_matching(wavelengths.lambda[i])
+                    * (self.values[i] / wavelengths.pdf[i]);
+            }
+        }
+        cie::xyz_to_rgb(&(xyz / (N_SAMPLES as f32 * cie::cie_y_integral())))
+    }
+}
+
+impl Copy for SampledSpectrum {}
+impl Clone for SampledSpectrum {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl ops::Add for SampledSpectrum {
+    type Output = SampledSpectrum;
+    fn add(self, rhs: SampledSpectrum) -> SampledSpectrum {
+        let mut values = self.values;
+        for (value, rhs) in values.iter_mut().zip(rhs.values) {
+            *value += rhs;
+        }
+        SampledSpectrum { values }
+    }
+}
+
+impl ops::Mul for SampledSpectrum {
+    type Output = SampledSpectrum;
+    fn mul(self, rhs: SampledSpectrum) -> SampledSpectrum {
+        let mut values = self.values;
+        for (value, rhs) in values.iter_mut().zip(rhs.values) {
+            *value *= rhs;
+        }
+        SampledSpectrum { values }
+    }
+}
@@ -0,0 +1,92 @@
+use crate::color::Color;
+
+// RGB to spectrum conversion of Smits 1999, "An RGB-to-Spectrum Conversion
+// for Reflectances". Basis spectra sampled in 10 bins over 380-720nm.
+const BIN_MIN: f32 = 380.0;
+const BIN_MAX: f32 = 720.0;
+const N_BINS: usize = 10;
+
+const WHITE: [f32; N_BINS] = [
+    1.0000, 1.0000, 0.9999, 0.9993, 0.9992, 0.9998, 1.0000, 1.0000, 1.0000, 1.0000,
+];
+const CYAN: [f32; N_BINS] = [
+    0.9710, 0.9426, 1.0007, 1.0007, 1.0007, 1.0007, 0.1564, 0.0000, 0.0000, 0.0000,
+];
+const MAGENTA: [f32; N_BINS] = [
+    1.0000, 1.0000, 0.9685, 0.2229, 0.0000, 0.0458, 0.8369, 1.0000, 1.0000, 0.9959,
+];
+const YELLOW: [f32; N_BINS] = [
+    0.0001, 0.0000, 0.1088, 0.6651, 1.0000, 1.0000, 0.9996, 0.9586, 0.9685, 0.9840,
+];
+const RED: [f32; N_BINS] = [
+    0.1012, 0.0515, 0.0000, 0.0000, 0.0000, 0.0000, 0.8325, 1.0149, 1.0149, 1.0149,
+];
+const GREEN: [f32; N_BINS] = [
+    0.0000, 0.0000, 0.0273, 0.7937, 1.0000, 0.9418, 0.1719, 0.0000, 0.0000, 0.0025,
+];
+const BLUE: [f32; N_BINS] = [
+    1.0000, 1.0000, 0.8916, 0.3323, 0.0000, 0.0000, 0.0003, 0.0369, 0.0483, 0.0496,
+];
+
+/// Value of the spectrum upsampled from `color` at the wavelength (nm).
+pub fn rgb_to_spectrum(color: &Color, lambda: f32) -> f32 {
+    let bin = (((lambda - BIN_MIN) / (BIN_MAX - BIN_MIN) * N_BINS as f32).floor() as isize)
+        .clamp(0, N_BINS as isize - 1) as usize;
+    let (r, g, b) = (color.x, color.y, color.z);
+
+    if r <= g && r <= b {
+        let base = r * WHITE[bin];
+        if g <= b {
+            base + (g - r) * CYAN[bin] + (b - g) * BLUE[bin]
+        } else {
+            base + (b - r) * CYAN[bin] + (g - b) * GREEN[bin]
+        }
+    } else if g <= r && g <= b {
+        let base = g * WHITE[bin];
+        if r <= b {
+            base + (r - g) * MAGENTA[bin] + (b - r) * BLUE[bin]
+        } else {
+            base + (b - g) * MAGENTA[bin] + (r - b) * RED[bin]
+        }
+    } else {
+        let base = b * WHITE[bin];
+        if r <= g {
+            base + (r - b) * YELLOW[bin] + (g - r) * GREEN[bin]
+        } else {
+            base + (g - b) * YELLOW[bin] + (r - g) * RED[bin]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spectrum::sampled::{SampledSpectrum, SampledWavelengths};
+
+    /// Upsample and convert back to RGB with many wavelength samples.
+    fn round_trip(color: &Color) -> Color {
+        let samples = 4000;
+        let mut rgb = Color::zero();
+        for i in 0..samples {
+            let wavelengths = SampledWavelengths::sample_uniform((i as f32 + 0.5) / samples as f32);
+            rgb += SampledSpectrum::from_rgb(color, &wavelengths).to_rgb(&wavelengths);
+        }
+        rgb / samples as f32
+    }
+
+    #[test]
+    fn should_keep_white_and_gray() {
+        let white = round_trip(&Color::from((1.0, 1.0, 1.0)));
+        assert!((white - Color::from((1.0, 1.0, 1.0))).norm() < 0.02);
+        let gray = round_trip(&Color::from((0.5, 0.5, 0.5)));
+        assert!((gray - Color::from((0.5, 0.5, 0.5))).norm() < 0.01);
+    }
+
+    #[test]
+    fn should_keep_hue_of_primaries() {
+        let red = round_trip(&Color::from((1.0, 0.0, 0.0)));
+        assert!(red.x > 0.5 && red.x > red.y * 3.0 && red.x > red.z * 3.0);
+        let blue = round_trip(&Color::from((0.0, 0.0, 1.0)));
+        assert!(blue.z > 0.5 && blue.z > blue.x * 3.0 && blue.z > blue.y * 3.0);
+    }
+}
@@ -0,0 +1,77 @@
+use std::path::Path;
+
+use image::{ImageResult, Rgba32FImage};
+
+use crate::color::Color;
+
+/// Texture backed by an image, sampled with bilinear filtering and repeated
+/// outside of the [0, 1] uv range. `v = 1` is the top row of the image.
+///
+/// Texels are stored as loaded (no gamma decoding), which is what normal and
+/// height maps expect.
+pub struct ImageTexture {
+    pub image: Rgba32FImage,
+}
+
+impl ImageTexture {
+    pub fn load(path: &Path) -> ImageResult<Self> {
+        Ok(ImageTexture {
+            image: image::open(path)?.into_rgba32f(),
+        })
+    }
+
+    fn texel(&self, x: i64, y: i64) -> [f32; 4] {
+        let width = self.image.width() as i64;
+        let height = self.image.height() as i64;
+        self.image
+            .get_pixel(x.rem_euclid(width) as u32, y.rem_euclid(height) as u32)
+            .0
+    }
+
+    /// Bilinearly interpolated RGBA value.
+    fn sample_rgba(&self, u: f32, v: f32) -> [f32; 4] {
+        let x = u * self.image.width() as f32 - 0.5;
+        let y = (1.0 - v) * self.image.height() as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let corners = [
+            (self.texel(x0, y0), (1.0 - fx) * (1.0 - fy)),
+            (self.texel(x0 + 1, y0), fx * (1.0 - fy)),
+            (self.texel(x0, y0 + 1), (1.0 - fx) * fy),
+            (self.texel(x0 + 1, y0 + 1), fx * fy),
+        ];
+        let mut rgba = [0.0; 4];
+        for (texel, weight) in corners {
+            for channel in 0..4 {
+                rgba[channel] += texel[channel] * weight;
+            }
+        }
+        rgba
+    }
+
+    pub fn sample(&self, u: f32, v: f32) -> Color {
+        let rgba = self.sample_rgba(u, v);
+        Color::from((rgba[0], rgba[1], rgba[2]))
+    }
+
+    /// Alpha channel, 1 for images without one.
+    pub fn sample_alpha(&self, u: f32, v: f32) -> f32 {
+        self.sample_rgba(u, v)[3]
+    }
+
+    /// Average of the color channels, for grayscale maps.
+    pub fn sample_gray(&self, u: f32, v: f32) -> f32 {
+        let color = self.sample(u, v);
+        (color.x + color.y + color.z) / 3.0
+    }
+
+    /// Size of one texel in uv space.
+    pub fn texel_size(&self) -> (f32, f32) {
+        (
+            1.0 / self.image.width() as f32,
+            1.0 / self.image.height() as f32,
+        )
+    }
+}
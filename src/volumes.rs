@@ -0,0 +1,2 @@
+pub mod density;
+pub mod voxel_grid;
@@ -0,0 +1,39 @@
+use crate::vectors::vector3::Point3;
+
+/// Spatially varying density of a participating medium.
+pub trait DensityField: Sync {
+    fn density(&self, point: Point3) -> f32;
+
+    /// Upper bound of `density` over the whole field. Used as the majorant
+    /// for delta/ratio tracking, so it must never be exceeded.
+    fn max_density(&self) -> f32;
+}
+
+/// Density field defined by an arbitrary function of the position.
+pub struct ProceduralDensity<F: Fn(Point3) -> f32 + Sync> {
+    pub function: F,
+    pub max_density: f32,
+}
+
+impl<F: Fn(Point3) -> f32 + Sync> DensityField for ProceduralDensity<F> {
+    fn density(&self, point: Point3) -> f32 {
+        (self.function)(point).clamp(0.0, self.max_density)
+    }
+    fn max_density(&self) -> f32 {
+        self.max_density
+    }
+}
+
+/// Uniform density field.
+pub struct ConstantDensity {
+    pub density: f32,
+}
+
+impl DensityField for ConstantDensity {
+    fn density(&self, _: Point3) -> f32 {
+        self.density
+    }
+    fn max_density(&self) -> f32 {
+        self.density
+    }
+}
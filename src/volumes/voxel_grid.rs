@@ -0,0 +1,248 @@
+use std::{
+    fs,
+    io::{self, Error, ErrorKind},
+    path::Path,
+};
+
+use crate::vectors::vector3::Point3;
+
+use super::density::DensityField;
+
+/// Sample type of the raw voxel data.
+pub enum VoxelType {
+    U8,
+    F32,
+}
+
+/// Dense 3D grid of densities mapped onto the axis-aligned box `[min, max]`.
+/// Voxels are stored x-fastest, then y, then z.
+pub struct VoxelGrid {
+    pub sizes: [usize; 3],
+    pub voxels: Vec<f32>,
+    pub min: Point3,
+    pub max: Point3,
+    max_voxel: f32,
+}
+
+impl VoxelGrid {
+    /// Panics unless every size is at least 1 and `voxels` holds exactly
+    /// `sizes[0] * sizes[1] * sizes[2]` values.
+    pub fn new(sizes: [usize; 3], voxels: Vec<f32>, min: Point3, max: Point3) -> Self {
+        assert!(sizes.iter().all(|&size| size >= 1), "empty voxel grid");
+        assert_eq!(sizes[0] * sizes[1] * sizes[2], voxels.len());
+        let max_voxel = voxels.iter().cloned().fold(0.0, f32::max);
+        VoxelGrid {
+            sizes,
+            voxels,
+            min,
+            max,
+            max_voxel,
+        }
+    }
+
+    /// Load headerless little-endian voxel data.
+    pub fn from_raw(
+        path: &Path,
+        sizes: [usize; 3],
+        voxel_type: VoxelType,
+        min: Point3,
+        max: Point3,
+    ) -> io::Result<Self> {
+        if sizes.contains(&0) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "voxel grid sizes must be at least 1",
+            ));
+        }
+        let bytes = fs::read(path)?;
+        let voxels = decode_voxels(&bytes, &voxel_type, sizes[0] * sizes[1] * sizes[2])?;
+        Ok(VoxelGrid::new(sizes, voxels, min, max))
+    }
+
+    /// Load a NRRD file with attached raw data.
+    /// Only `dimension: 3`, `encoding: raw` and little endian
+    /// `uchar`/`float` data are supported.
+    pub fn from_nrrd(path: &Path, min: Point3, max: Point3) -> io::Result<Self> {
+        let bytes = fs::read(path)?;
+        VoxelGrid::parse_nrrd(&bytes, min, max)
+    }
+
+    fn parse_nrrd(bytes: &[u8], min: Point3, max: Point3) -> io::Result<Self> {
+        let invalid = |message: &str| Error::new(ErrorKind::InvalidData, message.to_string());
+
+        let header_end = bytes
+            .windows(2)
+            .position(|w| w == b"\n\n")
+            .ok_or_else(|| invalid("NRRD header is not terminated"))?;
+        let header = std::str::from_utf8(&bytes[..header_end])
+            .map_err(|_| invalid("NRRD header is not valid UTF-8"))?;
+
+        let mut lines = header.lines();
+        if !lines.next().unwrap_or("").starts_with("NRRD") {
+            return Err(invalid("missing NRRD magic"));
+        }
+
+        let mut sizes: Option<[usize; 3]> = None;
+        let mut voxel_type: Option<VoxelType> = None;
+        for line in lines {
+            if line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim() {
+                "type" => {
+                    voxel_type = Some(match value {
+                        "uchar" | "unsigned char" | "uint8" | "uint8_t" => VoxelType::U8,
+                        "float" => VoxelType::F32,
+                        _ => return Err(invalid("unsupported NRRD type")),
+                    })
+                }
+                "dimension" if value != "3" => {
+                    return Err(invalid("only 3 dimensional NRRD is supported"))
+                }
+                "encoding" if value != "raw" => {
+                    return Err(invalid("only raw NRRD encoding is supported"))
+                }
+                "endian" if value != "little" => {
+                    return Err(invalid("only little endian NRRD is supported"))
+                }
+                "sizes" => {
+                    let parsed: Vec<usize> = value
+                        .split_whitespace()
+                        .map(|s| s.parse::<usize>())
+                        .collect::<Result<_, _>>()
+                        .map_err(|_| invalid("invalid NRRD sizes"))?;
+                    if parsed.len() != 3 || parsed.contains(&0) {
+                        return Err(invalid("invalid NRRD sizes"));
+                    }
+                    sizes = Some([parsed[0], parsed[1], parsed[2]]);
+                }
+                _ => {}
+            }
+        }
+
+        let sizes = sizes.ok_or_else(|| invalid("missing NRRD sizes"))?;
+        let voxel_type = voxel_type.ok_or_else(|| invalid("missing NRRD type"))?;
+        let voxels = decode_voxels(
+            &bytes[header_end + 2..],
+            &voxel_type,
+            sizes[0] * sizes[1] * sizes[2],
+        )?;
+        Ok(VoxelGrid::new(sizes, voxels, min, max))
+    }
+
+    fn voxel(&self, x: usize, y: usize, z: usize) -> f32 {
+        self.voxels[x + self.sizes[0] * (y + self.sizes[1] * z)]
+    }
+}
+
+fn decode_voxels(bytes: &[u8], voxel_type: &VoxelType, count: usize) -> io::Result<Vec<f32>> {
+    let voxels: Vec<f32> = match voxel_type {
+        VoxelType::U8 => bytes.iter().map(|b| *b as f32 / 255.0).collect(),
+        VoxelType::F32 => bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect(),
+    };
+    if voxels.len() < count {
+        return Err(Error::new(
+            ErrorKind::UnexpectedEof,
+            format!("expected {} voxels, found {}", count, voxels.len()),
+        ));
+    }
+    Ok(voxels[..count].to_vec())
+}
+
+impl DensityField for VoxelGrid {
+    /// Trilinearly interpolated density. Zero outside of the grid bounds.
+    fn density(&self, point: Point3) -> f32 {
+        let local = (point - self.min) / (self.max - self.min);
+        let mut base = [0usize; 3];
+        let mut frac = [0f32; 3];
+        for axis in 0..3 {
+            if !(0.0..=1.0).contains(&local[axis]) {
+                return 0.0;
+            }
+            // voxel centers are located at (i + 0.5) / size
+            let size = self.sizes[axis];
+            let p = (local[axis] * size as f32 - 0.5).clamp(0.0, (size - 1) as f32);
+            base[axis] = (p.floor() as usize).min(size.saturating_sub(2));
+            frac[axis] = p - base[axis] as f32;
+        }
+
+        let mut density = 0.0;
+        for corner in 0..8 {
+            let offset = [corner & 1, (corner >> 1) & 1, (corner >> 2) & 1];
+            let mut weight = 1.0;
+            let mut index = [0usize; 3];
+            for axis in 0..3 {
+                index[axis] = (base[axis] + offset[axis]).min(self.sizes[axis] - 1);
+                weight *= if offset[axis] == 1 {
+                    frac[axis]
+                } else {
+                    1.0 - frac[axis]
+                };
+            }
+            density += weight * self.voxel(index[0], index[1], index[2]);
+        }
+        density
+    }
+
+    fn max_density(&self) -> f32 {
+        self.max_voxel
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_nrrd() {
+        let mut bytes =
+            b"NRRD0004\n# comment\ntype: uchar\ndimension: 3\nsizes: 2 1 1\nencoding: raw\n\n"
+                .to_vec();
+        bytes.extend_from_slice(&[0, 255]);
+        let grid = VoxelGrid::parse_nrrd(
+            &bytes,
+            Point3::from((0.0, 0.0, 0.0)),
+            Point3::from((2.0, 1.0, 1.0)),
+        )
+        .unwrap();
+        assert_eq!(grid.sizes, [2, 1, 1]);
+        assert_eq!(grid.voxels, vec![0.0, 1.0]);
+        assert_eq!(grid.max_density(), 1.0);
+    }
+
+    #[test]
+    fn should_reject_truncated_nrrd() {
+        let bytes = b"NRRD0004\ntype: float\ndimension: 3\nsizes: 2 2 2\nencoding: raw\n\n\0\0";
+        let result = VoxelGrid::parse_nrrd(bytes, Point3::zero(), Point3::from((1.0, 1.0, 1.0)));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn should_reject_empty_nrrd() {
+        let bytes = b"NRRD0004\ntype: float\ndimension: 3\nsizes: 0 2 2\nencoding: raw\n\n";
+        let result = VoxelGrid::parse_nrrd(bytes, Point3::zero(), Point3::from((1.0, 1.0, 1.0)));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn should_interpolate_trilinearly() {
+        let grid = VoxelGrid::new(
+            [2, 1, 1],
+            vec![0.0, 1.0],
+            Point3::from((0.0, 0.0, 0.0)),
+            Point3::from((2.0, 1.0, 1.0)),
+        );
+        // voxel centers are at x = 0.5 and x = 1.5
+        assert_eq!(grid.density(Point3::from((0.5, 0.5, 0.5))), 0.0);
+        assert_eq!(grid.density(Point3::from((1.0, 0.5, 0.5))), 0.5);
+        assert_eq!(grid.density(Point3::from((1.5, 0.5, 0.5))), 1.0);
+        assert_eq!(grid.density(Point3::from((3.0, 0.5, 0.5))), 0.0);
+    }
+}